@@ -0,0 +1,111 @@
+//! Compares range queries on the spatial index with a full scan of all positions, which is how
+//! the states were searched before the index was added, and measures the same queries through
+//! `Store::near`, as used by the API (including locking the shards and filtering outdated
+//! states). Run with `cargo bench` inside the backend directory.
+
+use std::{collections::HashMap, hint::black_box};
+
+use above_me::{
+    ogn::{aprs::Status, Aircraft},
+    position::{calculate_distance, BoundingBox, Grid, Position},
+    store::Store,
+    time::get_current_timestamp,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Number of stored positions, roughly the number of aircraft tracked world-wide
+const POSITION_COUNT: usize = 50_000;
+/// Cell size in degrees, equal to the one of the API state store
+const GRID_CELL_SIZE: f64 = 1.0;
+/// Max. age of a status in seconds, long enough that no status gets outdated while benchmarking
+const MAX_AGE: u64 = 60 * 60;
+/// Searched ranges in km
+const RANGES: [f64; 3] = [10.0, 100.0, 1000.0];
+
+fn create_positions() -> HashMap<usize, Position> {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    (0..POSITION_COUNT)
+        .map(|key| {
+            let position = Position {
+                latitude: rng.gen_range(-90.0..90.0),
+                longitude: rng.gen_range(-180.0..180.0),
+            };
+
+            (key, position)
+        })
+        .collect()
+}
+
+fn range_queries(c: &mut Criterion) {
+    let positions = create_positions();
+
+    let mut grid = Grid::create(GRID_CELL_SIZE);
+    for (key, position) in &positions {
+        grid.insert(*key, position);
+    }
+
+    let center = Position {
+        latitude: 48.858222,
+        longitude: 2.2945,
+    };
+
+    let mut group = c.benchmark_group("range_query");
+
+    for range in RANGES {
+        group.bench_with_input(BenchmarkId::new("full_scan", range), &range, |b, &range| {
+            b.iter(|| {
+                positions
+                    .values()
+                    .filter(|position| calculate_distance(&center, position) <= range)
+                    .count()
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("grid", range), &range, |b, &range| {
+            b.iter(|| {
+                grid.query(&BoundingBox::around(&center, black_box(range)))
+                    .filter(|key| calculate_distance(&center, &positions[key]) <= range)
+                    .count()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn store_queries(c: &mut Criterion) {
+    let time_stamp = get_current_timestamp();
+
+    let store = Store::create(MAX_AGE);
+    for (key, position) in create_positions() {
+        store.insert(Status {
+            aircraft: Aircraft {
+                id: format!("{key:06X}"),
+                ..Default::default()
+            },
+            position,
+            time_stamp,
+            ..Default::default()
+        });
+    }
+
+    let center = Position {
+        latitude: 48.858222,
+        longitude: 2.2945,
+    };
+
+    let mut group = c.benchmark_group("store_near");
+
+    for range in RANGES {
+        group.bench_with_input(BenchmarkId::from_parameter(range), &range, |b, &range| {
+            b.iter(|| store.near(&center, black_box(range), |_, _| Some(())).len())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, range_queries, store_queries);
+criterion_main!(benches);
@@ -0,0 +1,26 @@
+#![no_main]
+
+use std::{collections::HashMap, sync::LazyLock};
+
+use above_me::ogn::{aprs, receiver, Aircraft, AircraftId, MetadataSource};
+use libfuzzer_sys::fuzz_target;
+
+static AIRCRAFT: LazyLock<HashMap<AircraftId, Aircraft>> = LazyLock::new(|| {
+    HashMap::from([(
+        String::from("DDE626"),
+        Aircraft {
+            id: String::from("DDE626"),
+            competition_number: Some(String::from("G1")),
+            registration: Some(String::from("D-6507")),
+            metadata_source: MetadataSource::Ddb,
+            ..Default::default()
+        },
+    )])
+});
+
+fuzz_target!(|data: &[u8]| {
+    let line = String::from_utf8_lossy(data);
+
+    let _ = aprs::convert(&line, &AIRCRAFT);
+    let _ = receiver::convert(&line);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use above_me::ogn::ddb;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let data = String::from_utf8_lossy(data);
+
+    let _ = ddb::convert(&data);
+    let _ = ddb::convert_json(&data);
+});
@@ -3,7 +3,9 @@ pub use state::App;
 
 mod routes {
     pub mod aircraft;
+    pub mod area;
     pub mod overview;
+    pub mod receivers;
 }
 
 mod server;
@@ -1,44 +1,82 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     Json,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     api::App,
-    ogn::{aprs::Status, Aircraft},
+    ogn::{
+        aprs::{GroundState, Source, Status},
+        AddressType, Aircraft,
+    },
     position::Position,
 };
 
+use super::area::{self, Rejection};
+
 /// Handler for route _/r/:latitude/:longitude/:range_
 ///
-/// Responds with a list of aircraft in the _:range_ around _:latitude_ and _:longitude_
+/// Responds with a list of aircraft in the _:range_ around _:latitude_ and _:longitude_,
+/// optionally filtered by signal quality (see `QualityFilter`). Responds with _400 Bad Request_
+/// if the parameters are invalid (see `area::validate`).
 pub async fn handler(
-    Path((latitude, longitude, range)): Path<(f32, f32, f32)>,
+    Path((latitude, longitude, range)): Path<(f64, f64, f64)>,
+    Query(quality_filter): Query<QualityFilter>,
     State(app): State<App>,
-) -> Json<Response> {
-    /* Ensure range can be used as f32 */
-    let position = Position {
-        latitude,
-        longitude,
-    };
+) -> Result<Json<Response>, Rejection> {
+    let position = area::validate(latitude, longitude, range)?;
 
-    Json(Response {
+    Ok(Json(Response {
         latitude,
         longitude,
         range,
-        states: app.get_filtered_status_dtos(&position, range),
-    })
+        states: app.get_filtered_status_dtos(&position, range, &quality_filter),
+    }))
+}
+
+/// Optional query parameters to filter out states with low confidence. States that don't contain
+/// the filtered value are filtered out as well.
+#[derive(Default, Deserialize)]
+pub struct QualityFilter {
+    /// Min. signal quality in _dB_
+    pub min_signal_quality: Option<f32>,
+    /// Max. number of bit errors
+    pub max_bit_errors: Option<u16>,
+    /// Max. horizontal GPS accuracy in _m_
+    pub max_gps_horizontal_accuracy: Option<u16>,
+}
+
+impl QualityFilter {
+    /// Checks whether a status matches the filter
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status to check
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_filtered_status_dtos_checks_quality`
+    pub fn matches(&self, status: &Status) -> bool {
+        self.min_signal_quality
+            .is_none_or(|min| status.signal_quality.is_some_and(|v| v >= min))
+            && self
+                .max_bit_errors
+                .is_none_or(|max| status.bit_errors.is_some_and(|v| v <= max))
+            && self
+                .max_gps_horizontal_accuracy
+                .is_none_or(|max| status.gps_horizontal_accuracy.is_some_and(|v| v <= max))
+    }
 }
 
 #[derive(Serialize)]
 pub struct Response {
     /// Equals given latitude parameter
-    latitude: f32,
+    latitude: f64,
     /// Equals given longitude parameter
-    longitude: f32,
+    longitude: f64,
     /// Equals given range parameter
-    range: f32,
+    range: f64,
     /// The aircraft states that match the given parameters
     states: Vec<StatusDto>,
 }
@@ -47,8 +85,12 @@ pub struct Response {
 /// requested postion in km.
 #[derive(Clone, Serialize)]
 pub struct StatusDto {
+    /// Network / device type that the status originates from
+    pub source: Source,
     /// Affected aircraft
     pub aircraft: Aircraft,
+    /// Type of the address that the aircraft is identified by
+    pub address_type: AddressType,
     /// Position of aircraft
     pub position: Position,
     /// Speed in _km/h_
@@ -56,29 +98,94 @@ pub struct StatusDto {
     /// Vertical speed in _m/s_
     pub vertical_speed: Option<f32>,
     /// Altitude in _m_
-    pub altitude: Option<u16>,
+    pub altitude: Option<i32>,
     /// Turn rate in _turns/min_
     pub turn_rate: Option<f32>,
     /// Course of aircraft
     pub course: Option<u16>,
+    /// Signal quality in _dB_
+    pub signal_quality: Option<f32>,
+    /// Number of bit errors that have been corrected
+    pub bit_errors: Option<u16>,
+    /// Frequency offset in _kHz_
+    pub frequency_offset: Option<f32>,
+    /// Horizontal GPS accuracy in _m_
+    pub gps_horizontal_accuracy: Option<u16>,
+    /// Vertical GPS accuracy in _m_
+    pub gps_vertical_accuracy: Option<u16>,
+    /// State of a FANET participant on the ground (e.g. landed)
+    pub ground_state: Option<GroundState>,
+    /// Timestamp of sending status (as given by the packet)
+    pub packet_time_stamp: Option<u64>,
     /// Timestamp of receiving status
     pub time_stamp: u64,
     /// Distance to given postion in km
-    pub distance: f32,
+    pub distance: f64,
+    /// Name of the receiver (ground station) that heard the aircraft
+    pub receiver: Option<String>,
+    /// Distance of the aircraft to the receiver in km, if the receiver position is known
+    pub receiver_distance: Option<f64>,
 }
 
 impl StatusDto {
-    pub fn from(status: &Status, distance: f32) -> Self {
+    pub fn from(status: &Status, distance: f64, receiver_distance: Option<f64>) -> Self {
         Self {
+            source: status.source,
             aircraft: status.aircraft.clone(),
+            address_type: status.address_type,
             position: status.position.clone(),
             speed: status.speed,
             vertical_speed: status.vertical_speed,
             altitude: status.altitude,
             turn_rate: status.turn_rate,
             course: status.course,
+            signal_quality: status.signal_quality,
+            bit_errors: status.bit_errors,
+            frequency_offset: status.frequency_offset,
+            gps_horizontal_accuracy: status.gps_horizontal_accuracy,
+            gps_vertical_accuracy: status.gps_vertical_accuracy,
+            ground_state: status.ground_state,
+            packet_time_stamp: status.packet_time_stamp,
             time_stamp: status.time_stamp,
             distance,
+            receiver: status.header.receiver.clone(),
+            receiver_distance,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn handler_rejects_invalid_parameters() {
+        for (latitude, longitude, range) in [
+            (90.5, 2.2945, 15.0),
+            (48.858222, -1e12, 5000.0),
+            (48.858222, 2.2945, -15.0),
+            (48.858222, 2.2945, f64::NAN),
+            (f64::INFINITY, 2.2945, 15.0),
+        ] {
+            let result = handler(
+                Path((latitude, longitude, range)),
+                Query(QualityFilter::default()),
+                State(App::create(Default::default())),
+            )
+            .await;
+
+            assert_eq!(result.err().unwrap().0, StatusCode::BAD_REQUEST);
+        }
+
+        let result = handler(
+            Path((48.858222, 2.2945, 15.0)),
+            Query(QualityFilter::default()),
+            State(App::create(Default::default())),
+        )
+        .await;
+
+        assert!(result.unwrap().states.is_empty());
+    }
+}
@@ -0,0 +1,88 @@
+use axum::http::StatusCode;
+
+use crate::position::Position;
+
+const MIN_LATITUDE: f64 = -90.0;
+const MAX_LATITUDE: f64 = 90.0;
+const MIN_LONGITUDE: f64 = -180.0;
+const MAX_LONGITUDE: f64 = 180.0;
+
+/// Response for requests with invalid parameters
+pub type Rejection = (StatusCode, &'static str);
+
+/// Validates the _:latitude_, _:longitude_ and _:range_ parameters of a route
+///
+/// # Arguments
+///
+/// * `latitude` - Latitude parameter, must be within -90 to 90
+/// * `longitude` - Longitude parameter, must be within -180 to 180
+/// * `range` - Range parameter in km, must be finite and not negative
+///
+/// # Returns
+///
+/// Returns the position of given `latitude` and `longitude`, or a response with the status
+/// _400 Bad Request_ if any parameter is invalid.
+///
+/// # Examples
+///
+/// * test `area::validate_works`
+pub fn validate(latitude: f64, longitude: f64, range: f64) -> Result<Position, Rejection> {
+    if !(MIN_LATITUDE..=MAX_LATITUDE).contains(&latitude) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Invalid latitude: must be within -90 and 90",
+        ));
+    }
+
+    if !(MIN_LONGITUDE..=MAX_LONGITUDE).contains(&longitude) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Invalid longitude: must be within -180 and 180",
+        ));
+    }
+
+    if !range.is_finite() || range < 0.0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Invalid range: must be finite and not negative",
+        ));
+    }
+
+    Ok(Position {
+        latitude,
+        longitude,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_works() {
+        for (latitude, longitude, range) in [
+            (48.858222, 2.2945, 15.0),
+            (-90.0, -180.0, 0.0),
+            (90.0, 180.0, 20_000.0),
+        ] {
+            let position = validate(latitude, longitude, range).unwrap();
+            assert_eq!(position.latitude, latitude);
+            assert_eq!(position.longitude, longitude);
+        }
+
+        for (latitude, longitude, range) in [
+            (90.1, 2.2945, 15.0),
+            (-1e12, 2.2945, 15.0),
+            (f64::NAN, 2.2945, 15.0),
+            (48.858222, 180.1, 15.0),
+            (48.858222, -1e12, 15.0),
+            (48.858222, f64::INFINITY, 15.0),
+            (48.858222, 2.2945, -1.0),
+            (48.858222, 2.2945, f64::NAN),
+            (48.858222, 2.2945, f64::INFINITY),
+        ] {
+            let result = validate(latitude, longitude, range);
+            assert_eq!(result.err().unwrap().0, StatusCode::BAD_REQUEST);
+        }
+    }
+}
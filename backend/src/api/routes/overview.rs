@@ -1,6 +1,6 @@
 use axum::{extract::State, Json};
 
-use crate::api::{App, state::Overview};
+use crate::api::{state::Overview, App};
 
 /// Handler for route _/status_
 ///
@@ -0,0 +1,99 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde::Serialize;
+
+use crate::{api::App, ogn::receiver::Receiver};
+
+use super::area::{self, Rejection};
+
+/// Handler for route _/receivers_
+///
+/// Responds with a list of all currently known receivers
+pub async fn handler(State(app): State<App>) -> Json<Vec<Receiver>> {
+    Json(app.get_receivers())
+}
+
+/// Handler for route _/receivers/near/:latitude/:longitude/:range_
+///
+/// Responds with a list of receivers in the _:range_ around _:latitude_ and _:longitude_.
+/// Responds with _400 Bad Request_ if the parameters are invalid (see `area::validate`).
+pub async fn near_handler(
+    Path((latitude, longitude, range)): Path<(f64, f64, f64)>,
+    State(app): State<App>,
+) -> Result<Json<NearResponse>, Rejection> {
+    let position = area::validate(latitude, longitude, range)?;
+
+    Ok(Json(NearResponse {
+        latitude,
+        longitude,
+        range,
+        receivers: app.get_receivers_near(&position, range),
+    }))
+}
+
+#[derive(Serialize)]
+pub struct NearResponse {
+    /// Equals given latitude parameter
+    latitude: f64,
+    /// Equals given longitude parameter
+    longitude: f64,
+    /// Equals given range parameter
+    range: f64,
+    /// The receivers that match the given parameters
+    receivers: Vec<ReceiverDto>,
+}
+
+/// Dto representation of a receiver, containing the distance to the requested postion in km.
+#[derive(Clone, Serialize)]
+pub struct ReceiverDto {
+    /// The receiver
+    #[serde(flatten)]
+    pub receiver: Receiver,
+    /// Distance to given postion in km
+    pub distance: f64,
+}
+
+impl ReceiverDto {
+    pub fn from(receiver: &Receiver, distance: f64) -> Self {
+        Self {
+            receiver: receiver.clone(),
+            distance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn near_handler_rejects_invalid_parameters() {
+        for (latitude, longitude, range) in [
+            (-90.5, 2.2945, 15.0),
+            (48.858222, 180.5, 15.0),
+            (48.858222, 2.2945, -15.0),
+            (48.858222, 2.2945, f64::INFINITY),
+            (f64::NAN, 2.2945, 15.0),
+        ] {
+            let result = near_handler(
+                Path((latitude, longitude, range)),
+                State(App::create(Default::default())),
+            )
+            .await;
+
+            assert_eq!(result.err().unwrap().0, StatusCode::BAD_REQUEST);
+        }
+
+        let result = near_handler(
+            Path((48.858222, 2.2945, 15.0)),
+            State(App::create(Default::default())),
+        )
+        .await;
+
+        assert!(result.unwrap().receivers.is_empty());
+    }
+}
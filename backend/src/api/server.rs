@@ -4,7 +4,7 @@ use axum::{routing::get, Router};
 use log::info;
 use tokio::{net::TcpListener, net::ToSocketAddrs, sync::oneshot};
 
-use super::routes::{aircraft, overview};
+use super::routes::{aircraft, overview, receivers};
 use super::state::App;
 
 /// Initializes a tcp server that serves our API
@@ -14,7 +14,7 @@ use super::state::App;
 /// * `address` - The address that the server will bind to
 /// * `app` - The `App` that the API will use for its data
 /// * `shutdown_rx` - A oneshot `Receiver<()>` that will shut down the server gracefully when a
-///    message is received.
+///   message is received.
 ///
 /// # Returns
 ///
@@ -39,7 +39,7 @@ use super::state::App;
 /// // Shuts down API server
 /// shutdown_tx.send(()).unwrap();
 /// ```
-pub async fn init<'a, A: ToSocketAddrs>(
+pub async fn init<A: ToSocketAddrs>(
     address: &A,
     app: App,
     shutdown_rx: oneshot::Receiver<()>,
@@ -47,6 +47,11 @@ pub async fn init<'a, A: ToSocketAddrs>(
     let app = Router::new()
         .route("/r/:latitude/:longitude/:range", get(aircraft::handler))
         .route("/status", get(overview::handler))
+        .route("/receivers", get(receivers::handler))
+        .route(
+            "/receivers/near/:latitude/:longitude/:range",
+            get(receivers::near_handler),
+        )
         .with_state(app);
 
     let listener = TcpListener::bind(address).await?;
@@ -1,30 +1,57 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet},
     sync::{
         atomic::{AtomicU64, Ordering},
-        Arc, Mutex, MutexGuard,
+        Arc, Mutex, RwLock,
     },
+    time::Duration,
 };
 
+use log::debug;
 use serde::Serialize;
+use tokio::time::interval;
 
 use crate::{
-    aprs::Status,
+    aprs::{ConnectionState, ParseError, Status, MAX_STATUS_AGE},
+    ogn::receiver::{Beacon, Receiver},
+    ogn::{
+        ddb::{Failure as DdbFailure, Source as DdbSource, State as DdbState},
+        find_aircraft, Aircraft, AircraftId, AircraftRegistry,
+    },
     position::{calculate_distance, Position},
+    store::Store,
     time::get_current_timestamp,
 };
 
-use super::routes::aircraft::StatusDto;
+use super::routes::{
+    aircraft::{QualityFilter, StatusDto},
+    receivers::ReceiverDto,
+};
 
-const MAX_AGE_DIFF: u64 = 60 * 5; /* 5 minutes */
+/// Interval in which outdated states are removed
+const EVICTION_INTERVAL: Duration = Duration::from_secs(10);
+/// Receivers send beacons every few minutes, so we keep them a bit longer than aircraft
+const MAX_RECEIVER_AGE_DIFF: u64 = 60 * 30; /* 30 minutes */
 
 /// Our shared application state for the API
 #[derive(Clone)]
 pub struct App {
     /// Reference to all currently stored states
-    states: Arc<Mutex<HashMap<String, Status>>>,
+    states: Arc<Store>,
+    /// Reference to the aircraft data that is currently in use
+    aircraft: Arc<AircraftRegistry>,
     /// Timestamp of last APRS line received
     last_aprs_update: Arc<AtomicU64>,
+    /// Current state of the connection per APRS upstream
+    aprs_connection_states: Arc<Mutex<BTreeMap<String, ConnectionState>>>,
+    /// Number of APRS lines that could not be converted per reason
+    parse_errors: Arc<Mutex<BTreeMap<ParseError, u64>>>,
+    /// Reference to all currently known receivers
+    receivers: Arc<RwLock<HashMap<String, Receiver>>>,
+    /// State of the aircraft data that is currently in use, if any
+    ddb_state: Arc<Mutex<Option<DdbState>>>,
+    /// Report of the last failed attempt to fetch the aircraft data, if it hasn't succeeded since
+    ddb_failure: Arc<Mutex<Option<DdbFailure>>>,
 }
 
 /// DTO for status overview
@@ -36,22 +63,41 @@ pub struct Overview {
     pub last_status_update: Option<u64>,
     /// Timestamp of last APRS update received
     pub last_aprs_update: Option<u64>,
+    /// Current state of the connection per APRS upstream
+    pub aprs_connection_states: BTreeMap<String, ConnectionState>,
+    /// Number of APRS lines that could not be converted per reason
+    pub parse_errors: BTreeMap<ParseError, u64>,
+    /// State of the aircraft data that is currently in use, if any
+    pub ddb: Option<DdbState>,
+    /// Report of the last failed attempt to fetch the aircraft data, if it hasn't succeeded since
+    pub ddb_failure: Option<DdbFailure>,
 }
 
 impl App {
     /// Creates a new `App`
     ///
+    /// # Arguments
+    ///
+    /// * `aircraft` - The aircraft data that is currently in use, shared with the APRS client
+    ///
     /// # Examples
     ///
     /// ```
     /// use api::App;
     ///
-    /// let app = App::create();
+    /// let aircraft = Arc::new(AircraftRegistry::default());
+    /// let app = App::create(aircraft.clone());
     /// ```
-    pub fn create() -> App {
+    pub fn create(aircraft: Arc<AircraftRegistry>) -> App {
         App {
-            states: Arc::new(Mutex::new(HashMap::new())),
+            states: Arc::new(Store::create(MAX_STATUS_AGE)),
+            aircraft,
             last_aprs_update: Arc::new(AtomicU64::new(0)),
+            aprs_connection_states: Arc::new(Mutex::new(BTreeMap::new())),
+            parse_errors: Arc::new(Mutex::new(BTreeMap::new())),
+            receivers: Arc::new(RwLock::new(HashMap::new())),
+            ddb_state: Arc::new(Mutex::new(None)),
+            ddb_failure: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -60,6 +106,7 @@ impl App {
     /// # Arguments
     /// * `position` - The position that should be searched for
     /// * `range` - Range around given `position` that should be searched for.
+    /// * `quality_filter` - Filter for states with low confidence
     ///
     /// # Returns
     ///
@@ -71,17 +118,38 @@ impl App {
     /// * test `state::get_filtered_states_checks_age`
     /// * test `state::get_filtered_states_checks_range`
     /// * test `state::get_filtered_states_orders_correctly`
-    pub fn get_filtered_status_dtos(&self, position: &Position, range: f32) -> Vec<StatusDto> {
-        let mut states = self.states.lock().expect("Mutex was poisoned");
+    /// * test `state::get_filtered_status_dtos_checks_quality`
+    /// * test `state::get_filtered_status_dtos_adds_receiver_distance`
+    pub fn get_filtered_status_dtos(
+        &self,
+        position: &Position,
+        range: f64,
+        quality_filter: &QualityFilter,
+    ) -> Vec<StatusDto> {
+        let mut status_dtos = self.states.near(position, range, |status, distance| {
+            quality_filter
+                .matches(status)
+                .then(|| StatusDto::from(status, distance, None))
+        });
 
-        App::remove_outdated_states(&mut states);
+        /* Only the receivers of the matched states are looked up, after the states have been
+         * searched, so that receiver beacons aren't blocked by the search */
+        let current_timestamp = get_current_timestamp();
+        let receivers = self.receivers.read().expect("RwLock was poisoned");
 
-        let mut status_dtos = states
-            .values()
-            .map(|status| (status, calculate_distance(position, &status.position)))
-            .filter(|&(_, distance)| distance <= range)
-            .map(|(status, distance)| StatusDto::from(status, distance))
-            .collect::<Vec<StatusDto>>();
+        for status_dto in &mut status_dtos {
+            status_dto.receiver_distance = status_dto
+                .receiver
+                .as_ref()
+                .and_then(|receiver_id| receivers.get(receiver_id))
+                .filter(|receiver| !is_outdated_receiver(receiver, current_timestamp))
+                .and_then(|receiver| receiver.position.as_ref())
+                .map(|receiver_position| {
+                    calculate_distance(receiver_position, &status_dto.position)
+                });
+        }
+
+        drop(receivers);
 
         status_dtos.sort_unstable_by(|status_dto_1, status_dto_2| {
             status_dto_1
@@ -93,7 +161,9 @@ impl App {
         status_dtos
     }
 
-    /// Stores / updates a new status in the `App`
+    /// Stores / updates a new status in the `App`. Statuses of aircraft that are invisible
+    /// according to the current aircraft data are discarded, as they may have been converted
+    /// (and queued) before the aircraft became invisible and got purged.
     ///
     /// # Arguments
     ///
@@ -103,12 +173,66 @@ impl App {
     ///
     /// * test `state::get_filtered_states_checks_age`
     /// * test `state::get_filtered_states_checks_range`
+    /// * test `state::push_status_discards_invisible_aircraft`
     pub fn push_status(&self, new_status: Status) {
-        let mut states = self.states.lock().expect("Mutex was poisoned");
+        if is_invisible(&self.aircraft.load(), &new_status) {
+            return;
+        }
+
+        self.states.insert(new_status);
+    }
+
+    /// Removes all stored states of given aircraft from the `App`, if they are invisible
+    /// according to the current aircraft data. States of other aircraft that use the same id
+    /// with another address type are kept. The aircraft data has to be updated before, so that
+    /// statuses that are still queued can't re-insert the aircraft (see `push_status`).
+    ///
+    /// # Arguments
+    ///
+    /// * `aircraft_ids` - Ids of the aircraft that became invisible
+    ///
+    /// # Examples
+    ///
+    /// * test `state::purge_aircraft_works`
+    pub fn purge_aircraft(&self, aircraft_ids: &HashSet<AircraftId>) {
+        let aircraft = self.aircraft.load();
 
-        App::remove_outdated_states(&mut states);
+        self.states.retain(|status| {
+            !aircraft_ids.contains(&status.aircraft.id) || !is_invisible(&aircraft, status)
+        });
+    }
+
+    /// Updates the state of the aircraft data that is currently in use in the `App`. Reports of
+    /// previously failed attempts to fetch the aircraft data are discarded, unless the data
+    /// originates from the cache file.
+    ///
+    /// # Arguments
+    ///
+    /// * `ddb_state` - State of the aircraft data
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_overview_works`
+    /// * test `state::push_ddb_state_resets_failure`
+    pub fn push_ddb_state(&self, ddb_state: DdbState) {
+        if ddb_state.source == DdbSource::Network {
+            *self.ddb_failure.lock().expect("Mutex was poisoned") = None;
+        }
 
-        states.insert(new_status.aircraft.id.clone(), new_status);
+        *self.ddb_state.lock().expect("Mutex was poisoned") = Some(ddb_state);
+    }
+
+    /// Stores the report of a failed attempt to fetch the aircraft data in the `App`
+    ///
+    /// # Arguments
+    ///
+    /// * `ddb_failure` - Report of the failed attempt
+    ///
+    /// # Examples
+    ///
+    /// * test `state::push_ddb_state_resets_failure`
+    pub fn push_ddb_failure(&self, ddb_failure: DdbFailure) {
+        *self.ddb_failure.lock().expect("Mutex was poisoned") = Some(ddb_failure);
     }
 
     /// Updates timestamp of latest APRS update in the `App`
@@ -124,6 +248,123 @@ impl App {
         self.last_aprs_update.store(timestamp, Ordering::Relaxed);
     }
 
+    /// Updates the current state of the connection to an APRS upstream in the `App`
+    ///
+    /// # Arguments
+    ///
+    /// * `upstream` - Name of the APRS upstream
+    /// * `connection_state` - Current state of the connection
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_overview_works`
+    pub fn push_aprs_connection_state(&self, upstream: String, connection_state: ConnectionState) {
+        self.aprs_connection_states
+            .lock()
+            .expect("Mutex was poisoned")
+            .insert(upstream, connection_state);
+    }
+
+    /// Counts an APRS line that could not be converted in the `App`
+    ///
+    /// # Arguments
+    ///
+    /// * `parse_error` - The reason why the line could not be converted
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_overview_works`
+    pub fn push_parse_error(&self, parse_error: ParseError) {
+        *self
+            .parse_errors
+            .lock()
+            .expect("Mutex was poisoned")
+            .entry(parse_error)
+            .or_insert(0) += 1;
+    }
+
+    /// Stores / updates the receiver that sent given beacon in the `App`
+    ///
+    /// # Arguments
+    ///
+    /// * `beacon` - The beacon that a receiver sent
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_receivers_merges_beacons`
+    pub fn push_receiver_beacon(&self, beacon: Beacon) {
+        self.receivers
+            .write()
+            .expect("RwLock was poisoned")
+            .entry(beacon.receiver_id().to_string())
+            .or_insert_with(|| Receiver::create(beacon.receiver_id().to_string()))
+            .update(beacon);
+    }
+
+    /// Returns all currently known receivers, sorted by their id
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_receivers_merges_beacons`
+    pub fn get_receivers(&self) -> Vec<Receiver> {
+        let current_timestamp = get_current_timestamp();
+
+        let mut receivers = self
+            .receivers
+            .read()
+            .expect("RwLock was poisoned")
+            .values()
+            .filter(|receiver| !is_outdated_receiver(receiver, current_timestamp))
+            .cloned()
+            .collect::<Vec<Receiver>>();
+        receivers.sort_unstable_by(|receiver_1, receiver_2| receiver_1.id.cmp(&receiver_2.id));
+
+        receivers
+    }
+
+    /// Returns the receivers within `range` around given `position` as dtos. Receivers without
+    /// a known position are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The position that should be searched for
+    /// * `range` - Range around given `position` that should be searched for.
+    ///
+    /// # Returns
+    ///
+    /// Returns dtos of the receivers within `range` around given `position`, sorted in ascending
+    /// oder by distance to `position`.
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_receivers_near_checks_range`
+    pub fn get_receivers_near(&self, position: &Position, range: f64) -> Vec<ReceiverDto> {
+        let current_timestamp = get_current_timestamp();
+        let receivers = self.receivers.read().expect("RwLock was poisoned");
+
+        let mut receiver_dtos = receivers
+            .values()
+            .filter(|receiver| !is_outdated_receiver(receiver, current_timestamp))
+            .filter_map(|receiver| {
+                let distance = calculate_distance(position, receiver.position.as_ref()?);
+                Some((receiver, distance))
+            })
+            .filter(|&(_, distance)| distance <= range)
+            .map(|(receiver, distance)| ReceiverDto::from(receiver, distance))
+            .collect::<Vec<ReceiverDto>>();
+
+        drop(receivers);
+
+        receiver_dtos.sort_unstable_by(|receiver_dto_1, receiver_dto_2| {
+            receiver_dto_1
+                .distance
+                .partial_cmp(&receiver_dto_2.distance)
+                .unwrap()
+        });
+
+        receiver_dtos
+    }
+
     /// Returns an overview of the currently stored states
     ///
     /// # Examples
@@ -138,47 +379,122 @@ impl App {
             v => Some(v),
         };
 
-        let mut states = self.states.lock().expect("Mutex was poisoned");
-        App::remove_outdated_states(&mut states);
+        let aprs_connection_states = self
+            .aprs_connection_states
+            .lock()
+            .expect("Mutex was poisoned")
+            .clone();
+
+        let parse_errors = self
+            .parse_errors
+            .lock()
+            .expect("Mutex was poisoned")
+            .clone();
+
+        let ddb = self.ddb_state.lock().expect("Mutex was poisoned").clone();
+        let ddb_failure = self.ddb_failure.lock().expect("Mutex was poisoned").clone();
 
         Overview {
-            count: states.len(),
-            last_status_update: states.values().map(|s| s.time_stamp).max(),
+            count: self.states.len(),
+            last_status_update: self.states.last_update(),
             last_aprs_update,
+            aprs_connection_states,
+            parse_errors,
+            ddb,
+            ddb_failure,
         }
     }
 
-    /// Removes outdated states (by max age)
+    /// Removes outdated states (by max age of their packet timestamp, see
+    /// `Status::effective_time_stamp`) and outdated receivers every `EVICTION_INTERVAL`.
+    /// Outdated states and receivers are never returned anyway, this just frees their memory.
+    /// Runs until the returned future is dropped.
     ///
-    /// # Arguments
+    /// # Examples
     ///
-    /// * `states` - `MutexGuard` of states map
-    fn remove_outdated_states(states: &mut MutexGuard<HashMap<String, Status>>) {
-        let current_timestamp = get_current_timestamp();
+    /// ```
+    /// use api::App;
+    ///
+    /// let app = App::create(Arc::default());
+    ///
+    /// select! {
+    ///     _ = app.evict_outdated_states() => {},
+    ///     _ = shutdown_rx => {}
+    /// }
+    /// ```
+    pub async fn evict_outdated_states(&self) {
+        let mut interval = interval(EVICTION_INTERVAL);
 
-        let outdated_keys = states
-            .values()
-            .filter(|e| current_timestamp - e.time_stamp > MAX_AGE_DIFF)
-            .map(|e| e.aircraft.id.clone())
-            .collect::<Vec<String>>();
+        loop {
+            interval.tick().await;
 
-        for key in outdated_keys {
-            states.remove(&key);
+            let count = self.states.remove_outdated();
+            if count > 0 {
+                debug!("Removed {count} outdated states");
+            }
+
+            let count = self.remove_outdated_receivers();
+            if count > 0 {
+                debug!("Removed {count} outdated receivers");
+            }
         }
     }
+
+    /// Removes receivers that haven't sent a beacon within `MAX_RECEIVER_AGE_DIFF`
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of removed receivers
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_receivers_checks_age`
+    fn remove_outdated_receivers(&self) -> usize {
+        let current_timestamp = get_current_timestamp();
+        let mut receivers = self.receivers.write().expect("RwLock was poisoned");
+        let previous_len = receivers.len();
+
+        receivers.retain(|_, receiver| !is_outdated_receiver(receiver, current_timestamp));
+
+        previous_len - receivers.len()
+    }
+}
+
+/// Checks whether a receiver hasn't sent a beacon within `MAX_RECEIVER_AGE_DIFF`
+///
+/// # Arguments
+///
+/// * `receiver` - The receiver to check
+/// * `current_timestamp` - The current timestamp
+fn is_outdated_receiver(receiver: &Receiver, current_timestamp: u64) -> bool {
+    current_timestamp.saturating_sub(receiver.last_update()) > MAX_RECEIVER_AGE_DIFF
+}
+
+/// Checks whether the aircraft of a status is invisible according to given aircraft data
+///
+/// # Arguments
+///
+/// * `aircraft` - Mapping of `AircraftId` => `Aircraft` that is currently in use
+/// * `status` - The status to check
+fn is_invisible(aircraft: &HashMap<AircraftId, Aircraft>, status: &Status) -> bool {
+    find_aircraft(aircraft, &status.aircraft.id, status.address_type).is_some_and(|a| !a.visible)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::ogn::Aircraft;
+    use crate::ogn::{
+        ddb::Rejections,
+        receiver::{self, Beacon},
+        AddressType, Aircraft,
+    };
 
     use super::*;
 
     #[test]
     fn get_filtered_status_dtos_checks_age() {
-        let sut = App::create();
+        let sut = App::create(Arc::default());
         let current_timestamp = get_current_timestamp();
-        let outdated_timestamp = current_timestamp - MAX_AGE_DIFF - 1;
+        let outdated_timestamp = current_timestamp - MAX_STATUS_AGE - 1;
 
         let position = Position {
             longitude: 48.858222,
@@ -196,14 +512,42 @@ mod tests {
             outdated_timestamp,
         ));
 
-        let result = sut.get_filtered_status_dtos(&position, 1.0);
+        let result = sut.get_filtered_status_dtos(&position, 1.0, &QualityFilter::default());
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].aircraft.id, "AB1234");
+    }
+
+    #[test]
+    fn get_filtered_status_dtos_checks_packet_age() {
+        let sut = App::create(Arc::default());
+        let current_timestamp = get_current_timestamp();
+        let outdated_timestamp = current_timestamp - MAX_STATUS_AGE - 1;
+
+        let position = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        let mut fresh_status =
+            create_status(String::from("AB1234"), position.clone(), current_timestamp);
+        fresh_status.packet_time_stamp = Some(current_timestamp - 2);
+
+        /* Received just now, but sent long ago (e.g. by a lagging server) */
+        let mut delayed_status =
+            create_status(String::from("CD5678"), position.clone(), current_timestamp);
+        delayed_status.packet_time_stamp = Some(outdated_timestamp);
+
+        sut.push_status(fresh_status);
+        sut.push_status(delayed_status);
+
+        let result = sut.get_filtered_status_dtos(&position, 1.0, &QualityFilter::default());
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].aircraft.id, "AB1234");
     }
 
     #[test]
     fn get_filtered_status_dtos_checks_range() {
-        let sut = App::create();
+        let sut = App::create(Arc::default());
         let current_timestamp = get_current_timestamp();
 
         let position = Position {
@@ -236,7 +580,7 @@ mod tests {
             current_timestamp,
         ));
 
-        let result = sut.get_filtered_status_dtos(&position, 4.0);
+        let result = sut.get_filtered_status_dtos(&position, 4.0, &QualityFilter::default());
 
         assert_eq!(result.len(), 2);
         assert!(result.iter().any(|s| s.aircraft.id == "AB1234"));
@@ -245,7 +589,7 @@ mod tests {
 
     #[test]
     fn get_filtered_status_dtos_orders_correctly() {
-        let sut = App::create();
+        let sut = App::create(Arc::default());
         let current_timestamp = get_current_timestamp();
 
         let position = Position {
@@ -280,7 +624,7 @@ mod tests {
             current_timestamp,
         ));
 
-        let result = sut.get_filtered_status_dtos(&position, 4.0);
+        let result = sut.get_filtered_status_dtos(&position, 4.0, &QualityFilter::default());
 
         assert_eq!(result.len(), 3);
         assert_eq!(result[0].aircraft.id, "AB1234");
@@ -288,9 +632,139 @@ mod tests {
         assert_eq!(result[2].aircraft.id, "EF9012");
     }
 
+    #[test]
+    fn get_filtered_status_dtos_separates_address_types() {
+        let sut = App::create(Arc::default());
+        let current_timestamp = get_current_timestamp();
+
+        let position = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        sut.push_status(create_status_with_address_type(
+            String::from("AB1234"),
+            AddressType::Flarm,
+            position.clone(),
+            current_timestamp,
+        ));
+
+        sut.push_status(create_status_with_address_type(
+            String::from("AB1234"),
+            AddressType::Icao,
+            position.clone(),
+            current_timestamp,
+        ));
+
+        sut.push_status(create_status_with_address_type(
+            String::from("AB1234"),
+            AddressType::Flarm,
+            position.clone(),
+            current_timestamp,
+        ));
+
+        let result = sut.get_filtered_status_dtos(&position, 1.0, &QualityFilter::default());
+
+        assert_eq!(result.len(), 2);
+        assert!(result.iter().any(|s| s.address_type == AddressType::Flarm));
+        assert!(result.iter().any(|s| s.address_type == AddressType::Icao));
+    }
+
+    #[test]
+    fn get_filtered_status_dtos_checks_quality() {
+        let sut = App::create(Arc::default());
+        let current_timestamp = get_current_timestamp();
+
+        let position = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        let mut good_status =
+            create_status(String::from("AB1234"), position.clone(), current_timestamp);
+        good_status.signal_quality = Some(20.0);
+        good_status.bit_errors = Some(0);
+        good_status.gps_horizontal_accuracy = Some(2);
+
+        let mut bad_status =
+            create_status(String::from("CD5678"), position.clone(), current_timestamp);
+        bad_status.signal_quality = Some(3.0);
+        bad_status.bit_errors = Some(5);
+        bad_status.gps_horizontal_accuracy = Some(20);
+
+        let unknown_status =
+            create_status(String::from("EF9012"), position.clone(), current_timestamp);
+
+        sut.push_status(good_status);
+        sut.push_status(bad_status);
+        sut.push_status(unknown_status);
+
+        let result = sut.get_filtered_status_dtos(&position, 1.0, &QualityFilter::default());
+        assert_eq!(result.len(), 3);
+
+        for quality_filter in [
+            QualityFilter {
+                min_signal_quality: Some(10.0),
+                ..QualityFilter::default()
+            },
+            QualityFilter {
+                max_bit_errors: Some(1),
+                ..QualityFilter::default()
+            },
+            QualityFilter {
+                max_gps_horizontal_accuracy: Some(5),
+                ..QualityFilter::default()
+            },
+        ] {
+            let result = sut.get_filtered_status_dtos(&position, 1.0, &quality_filter);
+            assert_eq!(result.len(), 1);
+            assert_eq!(result[0].aircraft.id, "AB1234");
+        }
+    }
+
+    #[test]
+    fn get_filtered_status_dtos_adds_receiver_distance() {
+        let sut = App::create(Arc::default());
+        let current_timestamp = get_current_timestamp();
+
+        sut.push_receiver_beacon(
+            receiver::convert(
+                "EDER>OGNSDR,TCPIP*,qAC,GLIDERN2:/101722h4851.49N/00217.67E&/A=000100",
+            )
+            .unwrap(),
+        );
+
+        let position = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        let mut known_receiver_status =
+            create_status(String::from("AB1234"), position.clone(), current_timestamp);
+        known_receiver_status.header.receiver = Some(String::from("EDER"));
+
+        let mut unknown_receiver_status =
+            create_status(String::from("CD5678"), position.clone(), current_timestamp);
+        unknown_receiver_status.header.receiver = Some(String::from("LFNW"));
+
+        sut.push_status(known_receiver_status);
+        sut.push_status(unknown_receiver_status);
+
+        let result = sut.get_filtered_status_dtos(&position, 1.0, &QualityFilter::default());
+        assert_eq!(result.len(), 2);
+
+        let known = result.iter().find(|s| s.aircraft.id == "AB1234").unwrap();
+        assert_eq!(known.receiver.as_deref(), Some("EDER"));
+        assert!(known.receiver_distance.is_some_and(|d| d > 0.0 && d < 10.0));
+
+        let unknown = result.iter().find(|s| s.aircraft.id == "CD5678").unwrap();
+        assert_eq!(unknown.receiver.as_deref(), Some("LFNW"));
+        assert_eq!(unknown.receiver_distance, None);
+    }
+
     #[test]
     fn get_overview_works() {
-        let sut = App::create();
+        let sut = App::create(Arc::default());
 
         let result_empty = sut.get_overview();
 
@@ -314,34 +788,275 @@ mod tests {
         ));
 
         sut.push_last_aprs_update_timestamp(current_timestamp);
+        sut.push_parse_error(ParseError::Stealth);
+        sut.push_parse_error(ParseError::NoPosition);
+        sut.push_parse_error(ParseError::Stealth);
+        sut.push_aprs_connection_state(
+            String::from("aprs.example.com"),
+            ConnectionState::Connected,
+        );
+        sut.push_ddb_state(DdbState {
+            source: DdbSource::Cache,
+            time_stamp: current_timestamp - 60,
+            count: 42,
+            rejected: Rejections::default(),
+        });
 
         let result_filled = sut.get_overview();
 
         assert_eq!(result_empty.count, 0);
         assert_eq!(result_empty.last_status_update, None);
         assert_eq!(result_empty.last_aprs_update, None);
+        assert!(result_empty.aprs_connection_states.is_empty());
+        assert!(result_empty.parse_errors.is_empty());
+        assert_eq!(result_empty.ddb, None);
 
         assert_eq!(result_filled.count, 2);
         assert_eq!(result_filled.last_status_update, Some(current_timestamp));
         assert_eq!(result_filled.last_aprs_update, Some(current_timestamp));
+        assert_eq!(
+            result_filled.aprs_connection_states.get("aprs.example.com"),
+            Some(&ConnectionState::Connected)
+        );
+        assert_eq!(
+            result_filled.parse_errors.get(&ParseError::Stealth),
+            Some(&2)
+        );
+        assert_eq!(
+            result_filled.parse_errors.get(&ParseError::NoPosition),
+            Some(&1)
+        );
+        assert_eq!(
+            result_filled.parse_errors.get(&ParseError::NoTracking),
+            None
+        );
+        assert_eq!(
+            result_filled.ddb,
+            Some(DdbState {
+                source: DdbSource::Cache,
+                time_stamp: current_timestamp - 60,
+                count: 42,
+                rejected: Rejections::default(),
+            })
+        );
+    }
+
+    #[test]
+    fn push_ddb_state_resets_failure() {
+        let sut = App::create(Arc::default());
+        let state = DdbState {
+            source: DdbSource::Cache,
+            time_stamp: 42,
+            count: 1,
+            rejected: Rejections::default(),
+        };
+
+        sut.push_ddb_failure(DdbFailure {
+            time_stamp: 43,
+            status: Some(503),
+            message: String::from("Unsuccessful response (503 Service Unavailable)"),
+        });
+        assert!(sut.get_overview().ddb_failure.is_some());
+
+        sut.push_ddb_state(state.clone());
+        assert!(sut.get_overview().ddb_failure.is_some());
+
+        sut.push_ddb_state(DdbState {
+            source: DdbSource::Network,
+            ..state
+        });
+        assert!(sut.get_overview().ddb_failure.is_none());
+    }
+
+    #[test]
+    fn get_receivers_merges_beacons() {
+        let sut = App::create(Arc::default());
+
+        for line in [
+            "LFNW>OGNSDR,TCPIP*,qAC,GLIDERN5:/101722h4321.05NI00128.68E&/A=000738",
+            "LFNW>OGNSDR,TCPIP*,qAC,GLIDERN5:>101722h v0.2.8.RPI-GPU CPU:0.5",
+            "EDER>OGNSDR,TCPIP*,qAC,GLIDERN2:>101722h v0.3.0.arm CPU:0.2",
+        ] {
+            sut.push_receiver_beacon(receiver::convert(line).unwrap());
+        }
+
+        let result = sut.get_receivers();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].id, "EDER");
+        assert!(result[0].position.is_none());
+        assert_eq!(result[1].id, "LFNW");
+        assert!(result[1].position.is_some());
+        assert_eq!(result[1].version.as_deref(), Some("0.2.8"));
+    }
+
+    #[test]
+    fn get_receivers_near_checks_range() {
+        let sut = App::create(Arc::default());
+
+        for line in [
+            "EDER>OGNSDR,TCPIP*,qAC,GLIDERN2:/101722h4851.49N/00217.67E&/A=000100",
+            "LFNW>OGNSDR,TCPIP*,qAC,GLIDERN5:/101722h4321.05NI00128.68E&/A=000738",
+            "EDXX>OGNSDR,TCPIP*,qAC,GLIDERN2:>101722h v0.3.0.arm CPU:0.2",
+        ] {
+            sut.push_receiver_beacon(receiver::convert(line).unwrap());
+        }
+
+        let position = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        let result = sut.get_receivers_near(&position, 10.0);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].receiver.id, "EDER");
+        assert!(result[0].distance < 10.0);
+    }
+
+    #[test]
+    fn get_receivers_checks_age() {
+        let sut = App::create(Arc::default());
+
+        let Some(Beacon::Position(mut beacon)) = receiver::convert(
+            "LFNW>OGNSDR,TCPIP*,qAC,GLIDERN5:/101722h4321.05NI00128.68E&/A=000738",
+        ) else {
+            panic!("Expected position beacon");
+        };
+        beacon.time_stamp = get_current_timestamp() - MAX_RECEIVER_AGE_DIFF - 1;
+
+        sut.push_receiver_beacon(Beacon::Position(beacon));
+
+        assert!(sut.get_receivers().is_empty());
+        assert_eq!(sut.remove_outdated_receivers(), 1);
+        assert_eq!(sut.remove_outdated_receivers(), 0);
+    }
+
+    #[test]
+    fn purge_aircraft_works() {
+        let aircraft = Arc::new(AircraftRegistry::default());
+        let sut = App::create(aircraft.clone());
+        let position = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        sut.push_status(create_status(
+            String::from("AAAAAA"),
+            position.clone(),
+            get_current_timestamp(),
+        ));
+        sut.push_status(create_status(
+            String::from("BBBBBB"),
+            position.clone(),
+            get_current_timestamp(),
+        ));
+        sut.push_status(create_status_with_address_type(
+            String::from("BBBBBB"),
+            AddressType::Icao,
+            position.clone(),
+            get_current_timestamp(),
+        ));
+
+        /* Only the FLARM aircraft "BBBBBB" became invisible, not the ICAO one */
+        aircraft.store(Arc::new(HashMap::from([(
+            String::from("BBBBBB"),
+            Aircraft {
+                id: String::from("BBBBBB"),
+                device_type: Some(AddressType::Flarm),
+                visible: false,
+                ..Default::default()
+            },
+        )])));
+        sut.purge_aircraft(&HashSet::from([String::from("BBBBBB")]));
+
+        let mut result = sut
+            .get_filtered_status_dtos(&position, 1.0, &QualityFilter::default())
+            .into_iter()
+            .map(|s| (s.aircraft.id.clone(), s.address_type))
+            .collect::<Vec<_>>();
+        result.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            result,
+            [
+                (String::from("AAAAAA"), AddressType::Flarm),
+                (String::from("BBBBBB"), AddressType::Icao)
+            ]
+        );
+    }
+
+    #[test]
+    fn push_status_discards_invisible_aircraft() {
+        let aircraft = Arc::new(AircraftRegistry::default());
+        let sut = App::create(aircraft.clone());
+        let position = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        /* Converted while the aircraft was still visible, but stored afterwards */
+        let status = create_status(
+            String::from("AAAAAA"),
+            position.clone(),
+            get_current_timestamp(),
+        );
+
+        let mut hidden = status.aircraft.clone();
+        hidden.device_type = Some(AddressType::Flarm);
+        hidden.visible = false;
+        aircraft.store(Arc::new(HashMap::from([(hidden.id.clone(), hidden)])));
+
+        sut.push_status(status);
+        sut.push_status(create_status(
+            String::from("BBBBBB"),
+            position.clone(),
+            get_current_timestamp(),
+        ));
+
+        /* Another aircraft that uses the same id with an ICAO address */
+        sut.push_status(create_status_with_address_type(
+            String::from("AAAAAA"),
+            AddressType::Icao,
+            position.clone(),
+            get_current_timestamp(),
+        ));
+
+        let mut result = sut
+            .get_filtered_status_dtos(&position, 1.0, &QualityFilter::default())
+            .into_iter()
+            .map(|s| (s.aircraft.id.clone(), s.address_type))
+            .collect::<Vec<_>>();
+        result.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            result,
+            [
+                (String::from("AAAAAA"), AddressType::Icao),
+                (String::from("BBBBBB"), AddressType::Flarm)
+            ]
+        );
     }
 
     fn create_status(aircraft_id: String, position: Position, time_stamp: u64) -> Status {
+        create_status_with_address_type(aircraft_id, AddressType::Flarm, position, time_stamp)
+    }
+
+    fn create_status_with_address_type(
+        aircraft_id: String,
+        address_type: AddressType,
+        position: Position,
+        time_stamp: u64,
+    ) -> Status {
         Status {
             aircraft: Aircraft {
                 id: aircraft_id,
-                call_sign: None,
-                registration: None,
-                model: None,
-                visible: true,
+                ..Default::default()
             },
+            address_type,
             position,
-            speed: None,
-            vertical_speed: None,
-            altitude: None,
-            turn_rate: None,
-            course: None,
             time_stamp,
+            ..Default::default()
         }
     }
 }
@@ -1,13 +1,15 @@
 use config::{ConfigError, Environment, File, FileFormat};
 use serde::Deserialize;
 
-use crate::aprs;
+use crate::{aprs, ogn::ddb};
 
 /// Name of the config file (".json" is added by the `config` crate automatically)
 pub const PROJECT_CONFIG_FILE: &str = "../config";
 pub const BACKEND_CONFIG_FILE: &str = "config";
 pub const ENVIRONMENT_PREFIX: &str = "ABOVE_ME";
 const ENVIRONMENT_SEPARATOR: &str = "__";
+/// Min. interval of requests to the DDB server (in seconds), so that it's not flooded
+const MIN_DDB_INTERVAL_SECONDS: u64 = 60;
 
 /// Representation of program configuration
 #[derive(Deserialize)]
@@ -16,10 +18,63 @@ pub struct Config {
     pub aprs: aprs::Config<String>,
     /// Url of the DDB server to fetch aircraft information
     pub ddb_url: String,
+    /// Format that the DDB server should respond with ("csv" or "json")
+    #[serde(default)]
+    pub ddb_format: ddb::Format,
+    /// Interval in seconds in which the aircraft information is refetched from the DDB server
+    #[serde(default = "default_ddb_refresh_interval_seconds")]
+    pub ddb_refresh_interval_seconds: u64,
+    /// Interval in seconds in which fetching the aircraft information is retried after failing
+    #[serde(default = "default_ddb_retry_interval_seconds")]
+    pub ddb_retry_interval_seconds: u64,
+    /// Timeout in seconds for connecting to the DDB server
+    #[serde(default = "default_ddb_connect_timeout_seconds")]
+    pub ddb_connect_timeout_seconds: u64,
+    /// Timeout in seconds for reading (a part of) the response of the DDB server
+    #[serde(default = "default_ddb_read_timeout_seconds")]
+    pub ddb_read_timeout_seconds: u64,
+    /// Number of retries of DDB requests that failed temporarily (e.g. 503 or timeout)
+    #[serde(default = "default_ddb_retries")]
+    pub ddb_retries: u32,
+    /// File to cache the aircraft information in, used if the DDB server is not reachable
+    #[serde(default)]
+    pub ddb_cache_file: Option<String>,
+    /// File with local aircraft information that takes precedence over the one of the DDB
+    #[serde(default)]
+    pub ddb_overrides_file: Option<String>,
+    /// Whether to start without aircraft information if neither the DDB server nor the cache
+    /// file is available (aircraft information is then fetched in the background)
+    #[serde(default)]
+    pub ddb_optional: bool,
     /// Url that the API server should bind to
     pub bind_to: String,
 }
 
+/// Returns the default of `Config::ddb_refresh_interval_seconds` (necessary for serde)
+fn default_ddb_refresh_interval_seconds() -> u64 {
+    60 * 60 /* 1 hour */
+}
+
+/// Returns the default of `Config::ddb_retry_interval_seconds` (necessary for serde)
+fn default_ddb_retry_interval_seconds() -> u64 {
+    60 /* 1 minute */
+}
+
+/// Returns the default of `Config::ddb_connect_timeout_seconds` (necessary for serde)
+fn default_ddb_connect_timeout_seconds() -> u64 {
+    10
+}
+
+/// Returns the default of `Config::ddb_read_timeout_seconds` (necessary for serde)
+fn default_ddb_read_timeout_seconds() -> u64 {
+    30
+}
+
+/// Returns the default of `Config::ddb_retries` (necessary for serde)
+fn default_ddb_retries() -> u32 {
+    2
+}
+
 /// Tries loading configuration from config files or environment
 ///
 /// # Examples
@@ -34,4 +89,161 @@ pub fn load() -> Result<Config, ConfigError> {
         .add_source(Environment::with_prefix(ENVIRONMENT_PREFIX).separator(ENVIRONMENT_SEPARATOR))
         .build()?
         .try_deserialize::<Config>()
+        .and_then(validate)
+}
+
+/// Checks values that would break the program, e.g. intervals of 0 seconds
+///
+/// # Arguments
+///
+/// * `config` - The loaded configuration
+///
+/// # Examples
+///
+/// * test `config::validate_rejects_invalid_intervals`
+fn validate(config: Config) -> Result<Config, ConfigError> {
+    for (name, value) in [
+        (
+            "aprs.idle_timeout_seconds",
+            config.aprs.idle_timeout_seconds,
+        ),
+        (
+            "aprs.keep_alive_interval_seconds",
+            config.aprs.keep_alive_interval_seconds,
+        ),
+    ] {
+        if value == 0 {
+            return Err(ConfigError::Message(format!("{name} must be at least 1")));
+        }
+    }
+
+    for (name, value) in [
+        (
+            "ddb_refresh_interval_seconds",
+            config.ddb_refresh_interval_seconds,
+        ),
+        (
+            "ddb_retry_interval_seconds",
+            config.ddb_retry_interval_seconds,
+        ),
+    ] {
+        if value < MIN_DDB_INTERVAL_SECONDS {
+            return Err(ConfigError::Message(format!(
+                "{name} must be at least {MIN_DDB_INTERVAL_SECONDS}"
+            )));
+        }
+    }
+
+    let reconnect = &config.aprs.reconnect;
+
+    if reconnect.initial_delay_ms == 0 {
+        return Err(ConfigError::Message(String::from(
+            "aprs.reconnect.initial_delay_ms must be at least 1",
+        )));
+    }
+
+    if reconnect.max_delay_ms < reconnect.initial_delay_ms {
+        return Err(ConfigError::Message(String::from(
+            "aprs.reconnect.max_delay_ms must be at least aprs.reconnect.initial_delay_ms",
+        )));
+    }
+
+    /* Ranges don't contain NaN, so it is rejected as well. */
+    if !(1.0..).contains(&reconnect.multiplier) {
+        return Err(ConfigError::Message(String::from(
+            "aprs.reconnect.multiplier must be at least 1",
+        )));
+    }
+
+    if !(0.0..=1.0).contains(&reconnect.jitter) {
+        return Err(ConfigError::Message(String::from(
+            "aprs.reconnect.jitter must be between 0 and 1",
+        )));
+    }
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    #[test]
+    fn validate_rejects_invalid_intervals() {
+        assert!(validate(create_config(json!({}))).is_ok());
+
+        for aprs in [
+            json!({ "idle_timeout_seconds": 0 }),
+            json!({ "keep_alive_interval_seconds": 0 }),
+            json!({ "reconnect": { "initial_delay_ms": 0 } }),
+            json!({ "reconnect": { "initial_delay_ms": 2000, "max_delay_ms": 1000 } }),
+            json!({ "reconnect": { "multiplier": 0.5 } }),
+            json!({ "reconnect": { "jitter": -0.1 } }),
+            json!({ "reconnect": { "jitter": 1.5 } }),
+        ] {
+            let result = validate(create_config(json!({ "aprs": aprs })));
+            assert!(result.is_err());
+        }
+
+        for overrides in [
+            json!({ "ddb_refresh_interval_seconds": 0 }),
+            json!({ "ddb_retry_interval_seconds": 0 }),
+            json!({ "ddb_retry_interval_seconds": MIN_DDB_INTERVAL_SECONDS - 1 }),
+        ] {
+            assert!(validate(create_config(overrides)).is_err());
+        }
+
+        let result = validate(create_config(
+            json!({ "ddb_retry_interval_seconds": MIN_DDB_INTERVAL_SECONDS }),
+        ));
+        assert!(result.is_ok());
+
+        let mut config = create_config(json!({}));
+        config.aprs.reconnect.multiplier = f64::NAN;
+        assert!(validate(config).is_err());
+
+        let result = validate(create_config(json!({
+            "aprs": {
+                "reconnect": {
+                    "initial_delay_ms": 1,
+                    "max_delay_ms": 1,
+                    "multiplier": 1.0,
+                    "jitter": 1.0
+                }
+            }
+        })));
+        assert!(result.is_ok());
+    }
+
+    /// Creates a `Config` with required values, merged with `overrides`
+    fn create_config(overrides: Value) -> Config {
+        let mut value = json!({
+            "aprs": {
+                "address": "aprs.example.com:14580",
+                "user_name": "N0CALL",
+                "password": "-1",
+                "client_id": "above_me 0.1"
+            },
+            "ddb_url": "https://example.com/aircraft",
+            "bind_to": "127.0.0.1:8000"
+        });
+
+        merge(&mut value, overrides);
+
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// Merges `overrides` into `value` recursively
+    fn merge(value: &mut Value, overrides: Value) {
+        match (value, overrides) {
+            (Value::Object(value), Value::Object(overrides)) => {
+                for (key, override_value) in overrides {
+                    merge(value.entry(key).or_insert(Value::Null), override_value);
+                }
+            }
+            (value, overrides) => *value = overrides,
+        }
+    }
 }
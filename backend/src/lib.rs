@@ -0,0 +1,7 @@
+//! Parsers, data types and the state store of above_me, shared by the server binary, the
+//! benchmarks and the fuzz targets
+
+pub mod ogn;
+pub mod position;
+pub mod store;
+pub mod time;
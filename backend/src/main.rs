@@ -1,17 +1,22 @@
-use log::{error, info};
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
+
+use log::{error, info, warn};
 use tokio::{
     select,
     sync::{mpsc, oneshot},
     task::JoinSet,
 };
 
-use crate::ogn::{aprs, ddb::fetch_aircraft};
+use above_me::{ogn, position, store, time};
+
+use crate::ogn::{
+    aprs,
+    ddb::{self, apply_overrides, load_aircraft, refresh_aircraft},
+    AircraftRegistry,
+};
 
 mod api;
 mod config;
-mod ogn;
-mod position;
-mod time;
 
 #[tokio::main]
 async fn main() {
@@ -29,26 +34,70 @@ async fn main() {
         }
     };
 
+    let ddb_config = ddb::Config {
+        url: config.ddb_url,
+        format: config.ddb_format,
+        client_id: config.aprs.client_id.clone(),
+        connect_timeout: Duration::from_secs(config.ddb_connect_timeout_seconds),
+        read_timeout: Duration::from_secs(config.ddb_read_timeout_seconds),
+        retries: config.ddb_retries,
+        refresh_interval: Duration::from_secs(config.ddb_refresh_interval_seconds),
+        retry_interval: Duration::from_secs(config.ddb_retry_interval_seconds),
+        cache_file: config.ddb_cache_file.map(PathBuf::from),
+        overrides_file: config.ddb_overrides_file.map(PathBuf::from),
+    };
+
+    let ddb_client = match ddb::Client::create(&ddb_config) {
+        Ok(c) => c,
+        Err(e) => {
+            error!("Could not create DDB client: {e}");
+            return;
+        }
+    };
+
     info!("Loading aircraft data...");
-    let aircraft = match fetch_aircraft(&config.ddb_url).await {
-        Ok(a) => {
-            info!("Loaded aircraft data successfully!");
-            a
+    let (loaded, ddb_failure) = load_aircraft(&ddb_config, &ddb_client).await;
+    let (aircraft, ddb_state) = match loaded {
+        Some((a, s)) => {
+            info!("Loaded aircraft data successfully ({:?})!", s.source);
+            (a, Some(s))
         }
-        Err(e) => {
-            error!("Could not fetch aircraft data: {e}");
+        None if config.ddb_optional => {
+            warn!("Could not load aircraft data, starting without it");
+
+            let mut aircraft = HashMap::new();
+            apply_overrides(&ddb_config, &mut aircraft).await;
+
+            (aircraft, None)
+        }
+        None => {
+            error!("Could not load aircraft data");
             return;
         }
     };
+    let aircraft = Arc::new(AircraftRegistry::from_pointee(aircraft));
 
     let mut join_set = JoinSet::new();
 
     let (shutdown_tx, shutdown_rx) = oneshot::channel();
     let (status_tx, mut status_rx) = mpsc::channel(32);
     let (line_received_tx, mut line_received_rx) = mpsc::channel(32);
-
-    let app = api::App::create();
+    let (connection_state_tx, mut connection_state_rx) = mpsc::channel(32);
+    let (parse_error_tx, mut parse_error_rx) = mpsc::channel(32);
+    let (receiver_beacon_tx, mut receiver_beacon_rx) = mpsc::channel(32);
+    let (hidden_aircraft_tx, mut hidden_aircraft_rx) = mpsc::channel(32);
+    let (ddb_state_tx, mut ddb_state_rx) = mpsc::channel(32);
+    let (ddb_failure_tx, mut ddb_failure_rx) = mpsc::channel(32);
+
+    let app = api::App::create(aircraft.clone());
+    if let Some(ddb_state) = &ddb_state {
+        app.push_ddb_state(ddb_state.clone());
+    }
+    if let Some(ddb_failure) = ddb_failure {
+        app.push_ddb_failure(ddb_failure);
+    }
     let app_update = app.clone();
+    let app_eviction = app.clone();
 
     join_set.spawn(async move {
         info!("Initializing API...");
@@ -63,15 +112,30 @@ async fn main() {
     join_set.spawn(async move {
         info!("Initializing APRS client...");
 
-        loop {
-            if let Err(e) = aprs::init(&config.aprs, &status_tx, &line_received_tx, &aircraft).await
-            {
-                error!("Client stopped with error: {e}");
-                break;
-            }
-
-            /* Server may disconnect us at some point. Just reconnect and carry on. */
-            info!("Client disconnected. Reconnecting...");
+        let senders = aprs::Senders {
+            status_tx,
+            line_received_tx,
+            connection_state_tx,
+            parse_error_tx,
+            receiver_beacon_tx,
+        };
+
+        let ddb_senders = ddb::Senders {
+            hidden_tx: hidden_aircraft_tx,
+            state_tx: ddb_state_tx,
+            failure_tx: ddb_failure_tx,
+        };
+
+        /* Refreshing the aircraft data and evicting outdated states is only necessary as long as
+         * the client is running */
+        select! {
+            result = aprs::supervise(&config.aprs, &senders, &aircraft) => {
+                if let Err(e) = result {
+                    error!("Client stopped with error: {e}");
+                }
+            },
+            _ = refresh_aircraft(&ddb_config, &ddb_client, &aircraft, &ddb_senders, ddb_state.as_ref()) => {}
+            _ = app_eviction.evict_outdated_states() => {}
         }
 
         shutdown_tx.send(()).unwrap();
@@ -88,6 +152,24 @@ async fn main() {
                 Some(timestamp) = line_received_rx.recv() => {
                     app_update.push_last_aprs_update_timestamp(timestamp);
                 },
+                Some(update) = connection_state_rx.recv() => {
+                    app_update.push_aprs_connection_state(update.upstream, update.state);
+                },
+                Some(parse_error) = parse_error_rx.recv() => {
+                    app_update.push_parse_error(parse_error);
+                },
+                Some(beacon) = receiver_beacon_rx.recv() => {
+                    app_update.push_receiver_beacon(beacon);
+                },
+                Some(aircraft_ids) = hidden_aircraft_rx.recv() => {
+                    app_update.purge_aircraft(&aircraft_ids);
+                },
+                Some(ddb_state) = ddb_state_rx.recv() => {
+                    app_update.push_ddb_state(ddb_state);
+                },
+                Some(ddb_failure) = ddb_failure_rx.recv() => {
+                    app_update.push_ddb_failure(ddb_failure);
+                },
                 else => break
             }
         }
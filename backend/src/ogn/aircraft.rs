@@ -1,5 +1,9 @@
-use std::fmt::{Display, Formatter, Result};
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter, Result},
+};
 
+use arc_swap::ArcSwap;
 use serde::Serialize;
 
 /// Representing information about an aircraft.
@@ -8,53 +12,124 @@ pub struct Aircraft {
     /// DDB id of the aircraft
     #[serde(skip_serializing)]
     pub id: String,
-    /// Call sign, e.g. "G1"
-    pub call_sign: Option<String>,
+    /// Competition number, e.g. "G1" (serialized as "call_sign" to keep the API compatible)
+    #[serde(rename = "call_sign")]
+    pub competition_number: Option<String>,
     /// Registration, e.g. "D-6507"
     pub registration: Option<String>,
     /// Aircraft model type, e.g. "ASK-21"
     pub model: Option<String>,
+    /// Name of the pilot, as sent by FANET devices (e.g. "Mario")
+    pub name: Option<String>,
+    /// Type of device that the aircraft is registered with in the DDB
+    #[serde(skip_serializing)]
+    pub device_type: Option<AddressType>,
+    /// Category of the aircraft, as registered in the DDB
+    pub category: Option<Category>,
+    /// Aircraft type code of the DDB, e.g. 1 for gliders
+    #[serde(skip_serializing)]
+    pub ddb_aircraft_type: Option<u8>,
+    /// Where the information about the aircraft originates from
+    pub metadata_source: MetadataSource,
     /// Should the aircraft be identified and tracked?
     #[serde(skip_serializing)]
     pub visible: bool,
 }
 
+impl Default for Aircraft {
+    /// Returns a visible aircraft without an id, that nothing is known about
+    fn default() -> Aircraft {
+        Aircraft {
+            id: String::new(),
+            competition_number: None,
+            registration: None,
+            model: None,
+            name: None,
+            device_type: None,
+            category: None,
+            ddb_aircraft_type: None,
+            metadata_source: MetadataSource::Aprs,
+            visible: true,
+        }
+    }
+}
+
 impl Aircraft {
     /// Clones `Aircraft` with a given `model` name
     ///
     /// # Arguments
     /// * `model` - The new model name that the resulting aircraft
-    ///    should have
+    ///   should have
     ///
     /// # Examples
     ///
     /// ```
+    /// # use above_me::ogn::Aircraft;
     /// let aircraft = Aircraft {
     ///     id: String::from("AB1234"),
-    ///     call_sign: String::from("G1"),
-    ///     registration: String::from("D-6507"),
-    ///     model: String::from(""),
-    ///     visible: true,
+    ///     model: None,
+    ///     ..Default::default()
     /// };
     ///
-    ///  let aircraft_with_model = aircraft.with_model(Some(String::new("ASK-21")));
+    /// let aircraft_with_model = aircraft.with_model(Some(String::from("ASK-21")));
     ///
-    /// assert!(aircraft_with_model.model.is_some());
-    /// assert_eq!(aircraft_with_model.model.unwrap(), "ASK-21");
+    /// assert_eq!(aircraft_with_model.model.as_deref(), Some("ASK-21"));
     /// ```
     pub fn with_model(&self, model: Option<String>) -> Aircraft {
         Aircraft {
-            id: self.id.clone(),
-            call_sign: self.call_sign.clone(),
-            registration: self.registration.clone(),
             model,
-            visible: self.visible,
+            ..self.clone()
         }
     }
+
+    /// Checks whether the information about the aircraft applies to an aircraft that is
+    /// identified by an address of given `address_type`, as the same id may be used e.g. by an
+    /// ICAO and a FLARM address. Aircraft without device type (e.g. only listed in the local
+    /// overrides file) apply to every address type.
+    ///
+    /// # Arguments
+    ///
+    /// * `address_type` - Type of the address that the aircraft is identified by
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use above_me::ogn::{AddressType, Aircraft};
+    /// let aircraft = Aircraft {
+    ///     device_type: Some(AddressType::Flarm),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert!(aircraft.applies_to(AddressType::Flarm));
+    /// assert!(!aircraft.applies_to(AddressType::Icao));
+    /// ```
+    pub fn applies_to(&self, address_type: AddressType) -> bool {
+        self.device_type.is_none_or(|t| t == address_type)
+    }
+}
+
+/// Returns the information about the aircraft that is identified by `id` and an address of given
+/// `address_type` (see `Aircraft::applies_to`)
+///
+/// # Arguments
+///
+/// * `aircraft` - Mapping of `Id` => `Aircraft`, e.g. as fetched from the DDB
+/// * `id` - Id of the aircraft
+/// * `address_type` - Type of the address that the aircraft is identified by
+///
+/// # Examples
+///
+/// * test `aircraft::find_checks_address_type`
+pub fn find<'a>(
+    aircraft: &'a HashMap<Id, Aircraft>,
+    id: &str,
+    address_type: AddressType,
+) -> Option<&'a Aircraft> {
+    aircraft.get(id).filter(|a| a.applies_to(address_type))
 }
 
 /// Representation of generic aicraft types.
-#[derive(PartialEq)]
+#[derive(Debug, PartialEq)]
 pub enum Type {
     Glider,
     Tow,
@@ -81,7 +156,8 @@ impl Type {
     ///
     /// # Examples
     /// ```
-    /// assert_eq!(Type::from_aprs_u8(15), Type::Obstacle);
+    /// # use above_me::ogn::AircraftType as Type;
+    /// assert_eq!(Type::from_aprs_u8(15), Some(Type::Obstacle));
     /// assert_eq!(Type::from_aprs_u8(0), None);
     /// ```
     ///
@@ -111,6 +187,7 @@ impl Type {
     ///
     /// # Examples
     /// ```
+    /// # use above_me::ogn::AircraftType as Type;
     /// assert_eq!(Type::Glider.get_name(), "(Motor) Glider");
     /// ```
     pub fn get_name(&self) -> &'static str {
@@ -132,15 +209,185 @@ impl Type {
     }
 }
 
+/// Origin of the information about an aircraft. If an aircraft is listed in the local overrides
+/// file, its values take precedence over the ones of the DDB, which in turn take precedence over
+/// the ones that the aircraft sends itself (e.g. the model derived from the APRS aircraft type).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataSource {
+    /// Aircraft is not known, only the values that it sends itself are available
+    Aprs,
+    /// Values originate from the DDB
+    Ddb,
+    /// At least one value originates from the local overrides file
+    Override,
+}
+
+/// Representation of the aircraft categories of the DDB.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    Glider,
+    Plane,
+    Ultralight,
+    Helicopter,
+    Drone,
+    Other,
+}
+
+impl Category {
+    /// Tries getting the category for the DDB aircraft type value
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - DDB aircraft type id
+    ///
+    /// # Examples
+    /// ```
+    /// # use above_me::ogn::AircraftCategory as Category;
+    /// assert_eq!(Category::from_ddb_u8(1), Some(Category::Glider));
+    /// assert_eq!(Category::from_ddb_u8(0), None);
+    /// ```
+    ///
+    /// # References
+    /// - [OGN DDB](https://ddb.glidernet.org/download/?t=1)
+    pub fn from_ddb_u8(id: u8) -> Option<Category> {
+        match id {
+            1 => Some(Self::Glider),
+            2 => Some(Self::Plane),
+            3 => Some(Self::Ultralight),
+            4 => Some(Self::Helicopter),
+            5 => Some(Self::Drone),
+            6 => Some(Self::Other),
+            _ => None,
+        }
+    }
+}
+
+/// Representation of the types of addresses that aircraft are identified by.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressType {
+    Random,
+    Icao,
+    /// Default, as most aircraft are identified by their FLARM address
+    #[default]
+    Flarm,
+    Ogn,
+}
+
+impl AddressType {
+    /// Tries getting the address type for the DDB device type value
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - DDB device type, e.g. "F"
+    ///
+    /// # Examples
+    /// ```
+    /// # use above_me::ogn::AddressType;
+    /// assert_eq!(AddressType::from_ddb_str("F"), Some(AddressType::Flarm));
+    /// assert_eq!(AddressType::from_ddb_str("X"), None);
+    /// ```
+    pub fn from_ddb_str(value: &str) -> Option<AddressType> {
+        match value {
+            "F" => Some(Self::Flarm),
+            "I" => Some(Self::Icao),
+            "O" => Some(Self::Ogn),
+            _ => None,
+        }
+    }
+
+    /// Gets the address type for the APRS address type value
+    /// (encoded inside the aircraft id field).
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Address type id, only the lowest two bits are used
+    ///
+    /// # Examples
+    /// ```
+    /// # use above_me::ogn::AddressType;
+    /// assert_eq!(AddressType::from_aprs_u8(2), AddressType::Flarm);
+    /// ```
+    ///
+    /// # References
+    /// - [OGN Wiki](http://wiki.glidernet.org/wiki:ogn-flavoured-aprs#toc2)
+    pub fn from_aprs_u8(id: u8) -> AddressType {
+        match id & 0b11 {
+            0 => Self::Random,
+            1 => Self::Icao,
+            2 => Self::Flarm,
+            _ => Self::Ogn,
+        }
+    }
+}
+
 /// Alias for `String`, just for readability.
 pub type Id = String;
 
+/// Mapping of `Id` => `Aircraft` that can be swapped atomically (e.g. on refreshing the DDB)
+/// while being read concurrently
+pub type Registry = ArcSwap<HashMap<Id, Aircraft>>;
+
 impl Display for Aircraft {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(
             f,
-            "[ Id: {}, Call sign: {:?}, Registration: {:?}, Type: {:?}, Visible: {} ]",
-            self.id, self.call_sign, self.registration, self.model, self.visible
+            "[ Id: {}, Competition number: {:?}, Registration: {:?}, Type: {:?}, Visible: {} ]",
+            self.id, self.competition_number, self.registration, self.model, self.visible
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aircraft_serializes_call_sign() {
+        let aircraft = Aircraft {
+            id: String::from("AB1234"),
+            competition_number: Some(String::from("G1")),
+            registration: Some(String::from("D-6507")),
+            model: Some(String::from("ASK-21")),
+            device_type: Some(AddressType::Flarm),
+            category: Some(Category::Glider),
+            ddb_aircraft_type: Some(1),
+            metadata_source: MetadataSource::Ddb,
+            ..Default::default()
+        };
+
+        let result = serde_json::to_value(&aircraft).unwrap();
+
+        assert_eq!(result["call_sign"], "G1");
+        assert!(result.get("competition_number").is_none());
+        assert!(result.get("id").is_none());
+    }
+
+    #[test]
+    fn find_checks_address_type() {
+        let aircraft = HashMap::from([
+            (
+                String::from("DDE626"),
+                Aircraft {
+                    id: String::from("DDE626"),
+                    device_type: Some(AddressType::Flarm),
+                    ..Default::default()
+                },
+            ),
+            (
+                String::from("AB1234"),
+                Aircraft {
+                    id: String::from("AB1234"),
+                    ..Default::default()
+                },
+            ),
+        ]);
+
+        assert!(find(&aircraft, "DDE626", AddressType::Flarm).is_some());
+        assert!(find(&aircraft, "DDE626", AddressType::Icao).is_none());
+        assert!(find(&aircraft, "AB1234", AddressType::Icao).is_some());
+        assert!(find(&aircraft, "CD5678", AddressType::Flarm).is_none());
+    }
+}
@@ -1,78 +1,209 @@
 use std::{
-    collections::HashMap,
+    fmt::{self, Display, Formatter},
     io::{Error, ErrorKind},
+    time::Duration,
 };
 
-use log::{debug, error};
+use log::debug;
 use serde::Deserialize;
-use tokio::io::{AsyncBufReadExt, BufReader, BufWriter};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader, BufWriter};
 use tokio::{
     io::AsyncWriteExt,
     net::{TcpStream, ToSocketAddrs},
+    select,
     sync::mpsc::Sender,
+    time::{interval_at, sleep_until, Instant, MissedTickBehavior},
 };
 
 use crate::{
-    ogn::{Aircraft, AircraftId},
+    ogn::{receiver, AircraftRegistry},
     time::get_current_timestamp,
 };
 
-use super::conversion::convert;
+use super::conversion::convert_packet;
+use super::error::ParseError;
+use super::fanet::{apply_name, convert_name, Names};
+use super::packet::parse_packet;
 use super::status::Status;
+use super::supervisor::{ConnectionStateUpdate, ReconnectConfig};
 
 /// Messages starting with a hashtag are comments (e.g. keep alive messages)
 const IDENTIFIER_COMMENT: char = '#';
 /// Messages starting with this sequence are connection details
 const IDENTIFIER_TCP_PACKET: &str = "TCPIP*";
-/// Approx. interval of keep alive messages to the server (in seconds)
-const KEEPALIVE_INTERVAL_SECONDS: u64 = 60 * 10;
+/// Default interval of keep alive messages to the server (in seconds)
+const DEFAULT_KEEPALIVE_INTERVAL_SECONDS: u64 = 60 * 10;
+/// Default time without any incoming line after which the connection is considered dead
+/// (in seconds). The server sends a keep alive every 20 - 30 seconds, so a minute is plenty.
+const DEFAULT_IDLE_TIMEOUT_SECONDS: u64 = 60;
+/// Lines of the server are separated by line breaks
+const IDENTIFIER_LINE_BREAK: u8 = b'\n';
+/// Max. length of a line (in bytes). APRS-IS limits packets to 512 bytes, so longer lines are
+/// garbage and would otherwise grow the buffer without limit.
+const MAX_LINE_LENGTH: usize = 1024;
 /// Keep alive message
 const KEEPALIVE_MESSAGE: &[u8; 12] = b"#keep alive\n";
 
-/// Configuration for connecting to an APRS server
+/// Configuration for connecting to APRS servers
 #[derive(Deserialize)]
 pub struct Config<A: ToSocketAddrs> {
-    /// Address to connect to, e.g. "aprs.example.com"
-    pub address: A,
+    /// Server (and filter) to connect to
+    #[serde(flatten)]
+    pub upstream: Upstream<A>,
+    /// Further servers (or filters) to connect to concurrently
+    #[serde(default)]
+    pub additional_upstreams: Vec<Upstream<A>>,
     /// User name for authentication
     pub user_name: String,
     /// Password for authentication
     pub password: String,
     /// Name of the application
     pub client_id: String,
+    /// Behaviour on reconnecting after the connection failed or got closed
+    #[serde(default)]
+    pub reconnect: ReconnectConfig,
+    /// Time without any incoming line (including keep alives of the server) after which the
+    /// connection is considered dead (in seconds)
+    #[serde(default = "default_idle_timeout_seconds")]
+    pub idle_timeout_seconds: u64,
+    /// Interval of keep alive messages to the server (in seconds)
+    #[serde(default = "default_keep_alive_interval_seconds")]
+    pub keep_alive_interval_seconds: u64,
+}
+
+/// An APRS server and the filter that will be applied on its connection
+#[derive(Deserialize)]
+pub struct Upstream<A: ToSocketAddrs> {
+    /// Address to connect to, e.g. "aprs.example.com"
+    pub address: A,
     /// APRS filter that will be applied
     pub filter: Option<String>,
 }
 
-/// Initiates a `TcpClient` that connects to an APRS server based on given `ClientConfig` and
-/// transmits incoming aircraft states. Sends incoming APRS states via `status_tx`.
+impl<A: ToSocketAddrs> Config<A> {
+    /// Returns all upstreams that should be connected to
+    pub fn upstreams(&self) -> impl Iterator<Item = &Upstream<A>> {
+        std::iter::once(&self.upstream).chain(self.additional_upstreams.iter())
+    }
+}
+
+impl<A: ToSocketAddrs + Display> Display for Upstream<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.filter {
+            Some(filter) => write!(f, "{} (filter: {})", self.address, filter),
+            None => write!(f, "{}", self.address),
+        }
+    }
+}
+
+/// Senders that the APRS client passes on everything with that it receives
+#[derive(Clone)]
+pub struct Senders {
+    /// Sends incoming aircraft states
+    pub status_tx: Sender<Status>,
+    /// Sends timestamps of incoming APRS lines
+    pub line_received_tx: Sender<u64>,
+    /// Sends changes of the connection states
+    pub connection_state_tx: Sender<ConnectionStateUpdate>,
+    /// Sends the reason for every line that could not be converted
+    pub parse_error_tx: Sender<ParseError>,
+    /// Sends incoming beacons of OGN receivers
+    pub receiver_beacon_tx: Sender<receiver::Beacon>,
+}
+
+/// Returns the default of `Config::idle_timeout_seconds` (necessary for serde)
+fn default_idle_timeout_seconds() -> u64 {
+    DEFAULT_IDLE_TIMEOUT_SECONDS
+}
+
+/// Returns the default of `Config::keep_alive_interval_seconds` (necessary for serde)
+fn default_keep_alive_interval_seconds() -> u64 {
+    DEFAULT_KEEPALIVE_INTERVAL_SECONDS
+}
+
+/// Connects to an APRS server based on given `Upstream` and logs in.
 ///
 /// # Arguments
 ///
-/// * `config` - Information on where to connect & login
-/// * `status_tx` - A `Sender<String>` that will send incoming states from the server
-/// * `line_received_tx` - A `Sender<u64>` that will send timestamps of incoming APRS
-///    lines from the server
-/// * `aircraft` - Mapping of `AircraftId` => `Aircraft`, necessary for conversion
+/// * `config` - Information on how to login
+/// * `upstream` - Information on where to connect & which filter to apply
 ///
 /// # Returns
 ///
-/// Future that will either result to () or Error when an error occurs.
+/// Future that will either result to the connected `TcpStream` or Error when an error occurs.
 ///
 /// # Examples
 ///
+/// ```ignore
+/// let config = aprs::Config { ... };
+/// let tcp_stream = connect(&config, &config.upstream)
+///     .await
+///     .expect("Could not connect");
 /// ```
-/// use ogn::{Aircraft, AircraftId};
+pub async fn connect<A: ToSocketAddrs>(
+    config: &Config<A>,
+    upstream: &Upstream<A>,
+) -> Result<TcpStream, Error> {
+    let mut tcp_stream = TcpStream::connect(&upstream.address).await?;
+
+    /* Login to server */
+    let login_message = if let Some(filter) = &upstream.filter {
+        format!(
+            "user {} pass {} vers {} filter {}\n",
+            config.user_name, config.password, config.client_id, filter
+        )
+    } else {
+        format!(
+            "user {} pass {} vers {}\n",
+            config.user_name, config.password, config.client_id
+        )
+    };
+
+    tcp_stream.write_all(login_message.as_bytes()).await?;
+    tcp_stream.flush().await?;
+
+    Ok(tcp_stream)
+}
+
+/// Receives lines of a connected APRS server and transmits incoming aircraft states via
+/// `senders.status_tx` and incoming receiver beacons via `senders.receiver_beacon_tx`.
+///
+/// # Arguments
+///
+/// * `tcp_stream` - The connected (and logged in) `TcpStream`, see `connect`
+/// * `config` - Information on idle timeout and keep alive interval
+/// * `senders` - The `Senders` that incoming states, receiver beacons, timestamps of incoming
+///   lines and parse errors will be sent with
+/// * `aircraft` - Mapping of `AircraftId` => `Aircraft`, necessary for conversion. The current
+///   mapping is loaded for every line, so it may be swapped while receiving.
+/// * `names` - Names of FANET aircraft, updated by incoming name packets and added to aircraft
+///   without DDB entry
+///
+/// # Returns
+///
+/// Future that will either result to () when the server closed the connection or Error when an
+/// error occurs. If no line has been received within `config.idle_timeout_seconds`, the
+/// connection is considered dead and an Error of kind `TimedOut` is returned. Lines longer than
+/// `MAX_LINE_LENGTH` result to an Error of kind `InvalidData`.
+///
+/// # Examples
+///
+/// ```ignore
+/// use ogn::AircraftRegistry;
 /// use std::collections::HashMap;
 /// use tokio::{spawn, sync::mpsc::channel};
 ///
-/// let config = aprs::ClientConfig { ... };
+/// let config = aprs::Config { ... };
 /// let (status_tx, status_rx) = channel(32);
-/// let (line_received_tx, line_received_rx) = channel(32);
-/// let aircraft: HashMap<AircraftId, Aircraft> = HashMap::new();
+/// let senders = aprs::Senders { status_tx, ... };
+/// let aircraft = AircraftRegistry::from_pointee(HashMap::new());
 ///
 /// spawn(async move {
-///     aprs::init(&config, &status_tx, &line_received_tx, &aircraft)
+///     let tcp_stream = connect(&config, &config.upstream)
+///         .await
+///         .expect("Could not connect");
+///
+///     receive(tcp_stream, &config, &senders, &aircraft, &Names::default())
 ///         .await
 ///         .expect("Client failed");
 /// });
@@ -81,95 +212,335 @@ pub struct Config<A: ToSocketAddrs> {
 ///     println!("Got status: {}", status);
 /// }
 /// ```
-pub async fn init<A: ToSocketAddrs>(
+pub async fn receive<A: ToSocketAddrs>(
+    mut tcp_stream: TcpStream,
     config: &Config<A>,
-    status_tx: &Sender<Status>,
-    line_received_tx: &Sender<u64>,
-    aircraft: &HashMap<AircraftId, Aircraft>,
+    senders: &Senders,
+    aircraft: &AircraftRegistry,
+    names: &Names,
 ) -> Result<(), Error> {
-    let mut tcp_stream = TcpStream::connect(&config.address).await?;
     let (mut read_half, mut write_half) = tcp_stream.split();
 
     let mut tcp_stream_reader = BufReader::new(&mut read_half);
     let mut tcp_stream_writer = BufWriter::new(&mut write_half);
 
-    /* Login to server */
-    let login_message = if let Some(filter) = &config.filter {
-        format!(
-            "user {} pass {} vers {} filter {}\n",
-            config.user_name, config.password, config.client_id, filter
-        )
-    } else {
-        format!(
-            "user {} pass {} vers {}\n",
-            config.user_name, config.password, config.client_id
-        )
-    };
+    let idle_timeout = Duration::from_secs(config.idle_timeout_seconds);
+    let keep_alive_interval = Duration::from_secs(config.keep_alive_interval_seconds);
+
+    /* The first tick of an interval completes immediately, but we've just logged in. */
+    let mut keep_alive = interval_at(Instant::now() + keep_alive_interval, keep_alive_interval);
+    keep_alive.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
-    let mut last_keep_alive_timestamp = get_current_timestamp();
+    let mut idle_deadline = Instant::now() + idle_timeout;
 
-    tcp_stream_writer
-        .write_all(login_message.as_bytes())
-        .await?;
-    tcp_stream_writer.flush().await?;
+    /* `read_until` is cancel safe as long as we keep the buffer between calls, which is
+     * necessary as it's raced against the keep alive and the idle timeout. */
+    let mut buffer = Vec::new();
 
     loop {
-        let mut line = String::new();
+        /* Reads at most up to `MAX_LINE_LENGTH`, including what has been read before. */
+        let limit = MAX_LINE_LENGTH.saturating_sub(buffer.len()) as u64;
+        let mut line_reader = (&mut tcp_stream_reader).take(limit);
+
+        select! {
+            result = line_reader.read_until(IDENTIFIER_LINE_BREAK, &mut buffer) => {
+                let count = result?;
+
+                if buffer.len() >= MAX_LINE_LENGTH && buffer.last() != Some(&IDENTIFIER_LINE_BREAK) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Line exceeds {MAX_LINE_LENGTH} bytes"),
+                    ));
+                }
 
-        match tcp_stream_reader.read_line(&mut line).await {
-            Ok(0) => {
-                debug!("Connection closed");
-                return Ok(());
+                if count == 0 {
+                    debug!("Connection closed");
+                    return Ok(());
+                }
             }
-            Ok(_) => (),
-            Err(e) => {
-                /* This may happen */
-                error!("Error while reading line: {e}");
+            _ = keep_alive.tick() => {
+                tcp_stream_writer.write_all(KEEPALIVE_MESSAGE).await?;
+                tcp_stream_writer.flush().await?;
+
+                debug!("Sent keep alive");
                 continue;
             }
-        };
+            () = sleep_until(idle_deadline) => {
+                return Err(Error::new(
+                    ErrorKind::TimedOut,
+                    format!("No line received within {} seconds", idle_timeout.as_secs()),
+                ));
+            }
+        }
+
+        idle_deadline = Instant::now() + idle_timeout;
+
+        let line = String::from_utf8_lossy(&buffer).into_owned();
+        buffer.clear();
 
         let current_timestamp = get_current_timestamp();
 
         debug!("Got line: '{line}'");
 
-        line_received_tx
+        senders
+            .line_received_tx
             .send(current_timestamp)
             .await
-            .or(Err(Error::new(
-                ErrorKind::Other,
-                "Could not send line received timestamp",
-            )))?;
-
-        /* APRS server sends a keep alive ever 20 - 30 seconds. As we don't want to worry about
-         * *another* async interval shit, we just check if the last keep alive was 10 - 11 minutes
-         * ago and, if so, send a new one. We won't run into a timeout if we're 30 seconds late,
-         * so KISS FTW. */
-        if current_timestamp - last_keep_alive_timestamp >= KEEPALIVE_INTERVAL_SECONDS {
-            last_keep_alive_timestamp = current_timestamp;
-
-            tcp_stream_writer.write_all(KEEPALIVE_MESSAGE).await?;
-            tcp_stream_writer.flush().await?;
-
-            debug!("Sent keep alive");
+            .or(Err(Error::other("Could not send line received timestamp")))?;
+
+        if line.starts_with(IDENTIFIER_COMMENT) {
+            continue;
         }
 
-        if line.starts_with(IDENTIFIER_COMMENT) || line.contains(IDENTIFIER_TCP_PACKET) {
+        if line.contains(IDENTIFIER_TCP_PACKET) {
+            /* Lines sent via TCP/IP aren't aircraft states but may be beacons of receivers. */
+            if let Some(beacon) = receiver::convert(&line) {
+                debug!("Passing beacon of receiver '{}'", beacon.receiver_id());
+
+                senders
+                    .receiver_beacon_tx
+                    .send(beacon)
+                    .await
+                    .or(Err(Error::other("Could not send receiver beacon")))?;
+            }
+
             continue;
         }
 
-        if let Some(status) = convert(&line, aircraft) {
-            if !status.aircraft.visible {
-                debug!("Got message for non-visible aircraft. Discard.");
+        let aircraft = aircraft.load();
+
+        /* Parse only once, as the packet is either a name or a status. */
+        let result = parse_packet(&line).and_then(|packet| {
+            if let Some((id, name)) = convert_name(&packet) {
+                debug!("Got name '{name}' for FANET aircraft '{id}'");
+
+                names.insert(id, name, current_timestamp);
+                return Ok(None);
+            }
+
+            convert_packet(packet, &aircraft).map(Some)
+        });
+
+        let mut status = match result {
+            Ok(Some(status)) => status,
+            Ok(None) => continue,
+            Err(e) => {
+                debug!("Line not parseable: {e}");
+
+                senders
+                    .parse_error_tx
+                    .send(e)
+                    .await
+                    .or(Err(Error::other("Could not send parse error")))?;
+
                 continue;
             }
+        };
 
-            debug!("Passing message for aircraft '{}'", status.aircraft.id);
+        if !status.aircraft.visible {
+            debug!("Got message for non-visible aircraft. Discard.");
+            continue;
+        }
 
-            status_tx
-                .send(status)
-                .await
-                .or(Err(Error::new(ErrorKind::Other, "Could not send status")))?;
+        apply_name(&mut status, &aircraft, names);
+
+        debug!("Passing message for aircraft '{}'", status.aircraft.id);
+
+        senders
+            .status_tx
+            .send(status)
+            .await
+            .or(Err(Error::other("Could not send status")))?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::{
+        io::AsyncReadExt,
+        net::TcpListener,
+        sync::mpsc::{channel, Receiver},
+        time::sleep,
+    };
+
+    use super::*;
+
+    async fn create_connection(
+        idle_timeout_seconds: u64,
+        keep_alive_interval_seconds: u64,
+    ) -> (Config<String>, TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let client = TcpStream::connect(&address).await.unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+
+        let config = Config {
+            upstream: Upstream {
+                address,
+                filter: None,
+            },
+            additional_upstreams: Vec::new(),
+            user_name: String::from("N0CALL"),
+            password: String::from("-1"),
+            client_id: String::from("test 0.1"),
+            reconnect: ReconnectConfig::default(),
+            idle_timeout_seconds,
+            keep_alive_interval_seconds,
+        };
+
+        (config, client, server)
+    }
+
+    fn create_senders() -> (
+        Senders,
+        Receiver<Status>,
+        Receiver<u64>,
+        Receiver<receiver::Beacon>,
+    ) {
+        let (status_tx, status_rx) = channel(32);
+        let (line_received_tx, line_received_rx) = channel(32);
+        let (connection_state_tx, _) = channel(32);
+        let (parse_error_tx, _) = channel(32);
+        let (receiver_beacon_tx, receiver_beacon_rx) = channel(32);
+
+        let senders = Senders {
+            status_tx,
+            line_received_tx,
+            connection_state_tx,
+            parse_error_tx,
+            receiver_beacon_tx,
+        };
+
+        (senders, status_rx, line_received_rx, receiver_beacon_rx)
+    }
+
+    #[tokio::test]
+    async fn receive_times_out_without_lines() {
+        let (config, client, _server) = create_connection(1, 60).await;
+        let (senders, _status_rx, _line_received_rx, _receiver_beacon_rx) = create_senders();
+
+        let result = receive(
+            client,
+            &config,
+            &senders,
+            &AircraftRegistry::default(),
+            &Names::default(),
+        )
+        .await;
+
+        assert!(result.is_err_and(|e| e.kind() == ErrorKind::TimedOut));
+    }
+
+    #[tokio::test]
+    async fn receive_resets_timeout_on_server_keep_alive() {
+        let (config, client, mut server) = create_connection(1, 60).await;
+        let (senders, _status_rx, mut line_received_rx, _receiver_beacon_rx) = create_senders();
+
+        tokio::spawn(async move {
+            for _ in 0..3 {
+                sleep(Duration::from_millis(500)).await;
+                server.write_all(b"# aprsc 2.1.14\n").await.unwrap();
+            }
+        });
+
+        let result = receive(
+            client,
+            &config,
+            &senders,
+            &AircraftRegistry::default(),
+            &Names::default(),
+        )
+        .await;
+
+        /* Server task ends and closes the connection after 1.5 seconds */
+        assert!(result.is_ok());
+        assert!(line_received_rx.recv().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn receive_sends_keep_alive_without_incoming_lines() {
+        let (config, client, mut server) = create_connection(5, 1).await;
+        let (senders, _status_rx, _line_received_rx, _receiver_beacon_rx) = create_senders();
+        let aircraft = AircraftRegistry::default();
+        let names = Names::default();
+
+        let server_task = tokio::spawn(async move {
+            let mut buffer = [0; KEEPALIVE_MESSAGE.len()];
+            server.read_exact(&mut buffer).await.unwrap();
+
+            /* Return the server as well, so the connection isn't closed before we checked. */
+            (buffer, server)
+        });
+
+        select! {
+            _ = receive(client, &config, &senders, &aircraft, &names) => {
+                panic!("Client stopped unexpectedly");
+            }
+            result = server_task => {
+                assert_eq!(&result.unwrap().0, KEEPALIVE_MESSAGE);
+            }
         }
     }
+
+    #[tokio::test]
+    async fn receive_passes_receiver_beacons() {
+        let (config, client, mut server) = create_connection(5, 60).await;
+        let (senders, mut status_rx, _line_received_rx, mut receiver_beacon_rx) = create_senders();
+
+        tokio::spawn(async move {
+            server
+                .write_all(
+                    b"LFNW>OGNSDR,TCPIP*,qAC,GLIDERN5:/101722h4321.05NI00128.68E&/A=000738\n",
+                )
+                .await
+                .unwrap();
+        });
+
+        let result = receive(
+            client,
+            &config,
+            &senders,
+            &AircraftRegistry::default(),
+            &Names::default(),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert!(receiver_beacon_rx
+            .recv()
+            .await
+            .is_some_and(|b| b.receiver_id() == "LFNW"));
+        assert!(status_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn receive_rejects_too_long_lines() {
+        let (config, client, mut server) = create_connection(5, 60).await;
+        let (senders, _status_rx, mut line_received_rx, _receiver_beacon_rx) = create_senders();
+
+        tokio::spawn(async move {
+            server
+                .write_all(&[b'A'; MAX_LINE_LENGTH * 2])
+                .await
+                .unwrap();
+
+            /* Keep the connection open, so that only the line length can stop the client. */
+            sleep(Duration::from_secs(5)).await;
+        });
+
+        let result = receive(
+            client,
+            &config,
+            &senders,
+            &AircraftRegistry::default(),
+            &Names::default(),
+        )
+        .await;
+
+        assert!(result.is_err_and(|e| e.kind() == ErrorKind::InvalidData));
+        assert!(line_received_rx.try_recv().is_err());
+    }
 }
@@ -1,39 +1,89 @@
-use std::{collections::HashMap, sync::LazyLock};
+use std::collections::HashMap;
 
 use log::debug;
-use regex::{Captures, Regex};
 
 use crate::{
-    ogn::{Aircraft, AircraftId, AircraftType},
+    ogn::{find_aircraft, AddressType, Aircraft, AircraftId, AircraftType},
     position::Position,
-    time::get_current_timestamp,
+    time::{get_current_timestamp, get_timestamp_by_time_of_day},
 };
 
-use super::status::Status;
-
-/// Regex pattern to extract data from valid APRS messages
-///
-/// # Notes
-///
-/// At the part of "idXXYYYYYY", "XX" must not be 40 or higher!
-/// This is due to the fact that this 2-digit hex number contains the tracking-information as
-/// _binary_ in the form of _0bSTxxxxxx_ and if _S_ = _1_ or _T_ = _1_, we should discard the
-/// message. So all "allowed" values are in the range of _0b00000000_ - _0b00111111_, or in hex:
-/// _0x00_ - _0x3f_, therefore we can discard all messages not in this range.
-///
-/// see: [dbursem/ogn-client-php](https://github.com/dbursem/ogn-client-php/blob/master/lib/OGNClient.php#L87)
-const LINE_PATTERN: &str = r"h(?<latitude>[0-9.]+[NS])[/\\]?.(?<longitude>[0-9.]+[WE]).(?:(?<course>\d{3})/(?<speed>\d{3})/A=(?<altitude>\d+))?.*?id(?<type>[0-3]{1}[A-Fa-f0-9]{1})(?<id>[A-Za-z0-9]+)(?: (?<verticalSpeed>[-+0-9]+)fpm)?(?: (?<turnRate>[-+.0-9]+)rot)?";
+use super::{
+    error::ParseError,
+    fanet::get_ground_state,
+    packet::{parse_packet, Body, Packet},
+    source::Source,
+    status::Status,
+};
 
 /// Factor to convert knots to km/h
-const FACTOR_KNOTS_TO_KM_H: f32 = 1.852;
+const FACTOR_KNOTS_TO_KM_H: f64 = 1.852;
 /// Factor to convert ft to m
-const FACTOR_FT_TO_M: f32 = 0.3048;
+const FACTOR_FT_TO_M: f64 = 0.3048;
 /// Factor to convert ft/min to m/s
 const FACTOR_FT_MIN_TO_M_SEC: f32 = 0.00508;
 /// Factor to convert "turns/2min" to "turns/min"
 const FACTOR_TURNS_TWO_MIN_TO_TURNS_MIN: f32 = 0.5;
+/// Bit of the id details that indicates stealth mode
+const MASK_STEALTH: u8 = 0b1000_0000;
+/// Bit of the id details that indicates no-tracking mode
+const MASK_NO_TRACKING: u8 = 0b0100_0000;
+/// Bits of the id details that contain the aircraft type (after shifting by `SHIFT_AIRCRAFT_TYPE`)
+const MASK_AIRCRAFT_TYPE: u8 = 0b1111;
+/// Bits of the id details that contain the address type
+const MASK_ADDRESS_TYPE: u8 = 0b11;
+/// Position of the aircraft type bits inside the id details
+const SHIFT_AIRCRAFT_TYPE: u8 = 2;
+
+/// Decoded "XX" part of the aircraft id field "idXXYYYYYY"
+///
+/// "XX" is a 2-digit hex value built like this: _0bSTttttaa_ where _S_ indicates stealth mode
+/// (should *NEVER* be sent over OGN), _T_ is no-tracking mode (should *NEVER* be parsed by
+/// above_me), _tttt_ is the aircraft type and _aa_ is the address type.
+///
+/// # References
+///
+/// - [OGN Wiki](http://wiki.glidernet.org/wiki:ogn-flavoured-aprs#toc2)
+#[derive(Debug, PartialEq)]
+pub(super) struct IdDetails {
+    /// Aircraft has stealth mode active
+    stealth: bool,
+    /// Aircraft has no-tracking mode active
+    no_tracking: bool,
+    /// Type of aircraft, if known
+    pub aircraft_type: Option<AircraftType>,
+    /// Type of the aircraft address
+    pub address_type: AddressType,
+}
 
-static LINE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(LINE_PATTERN).unwrap());
+impl IdDetails {
+    /// Decodes the "XX" part of the aircraft id field
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value of "XX"
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let id_details = IdDetails::from_u8(0x2D);
+    ///
+    /// assert!(!id_details.stealth);
+    /// assert!(!id_details.no_tracking);
+    /// assert_eq!(id_details.aircraft_type, Some(AircraftType::Balloon));
+    /// assert_eq!(id_details.address_type, AddressType::Icao);
+    /// ```
+    fn from_u8(value: u8) -> IdDetails {
+        IdDetails {
+            stealth: value & MASK_STEALTH != 0,
+            no_tracking: value & MASK_NO_TRACKING != 0,
+            aircraft_type: AircraftType::from_aprs_u8(
+                (value >> SHIFT_AIRCRAFT_TYPE) & MASK_AIRCRAFT_TYPE,
+            ),
+            address_type: AddressType::from_aprs_u8(value & MASK_ADDRESS_TYPE),
+        }
+    }
+}
 
 /// Tries converting an APRS line into a `Status`
 ///
@@ -45,15 +95,16 @@ static LINE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(LINE_PATTERN).u
 /// # Examples
 ///
 /// ```
-/// use ogn::{Aircraft, AircraftId}
 /// use std::collections::HashMap;
 ///
+/// use above_me::ogn::{aprs::convert, Aircraft};
+///
 /// let aircraft = Aircraft {
 ///     id: String::from("AB1234"),
-///     call_sign: String::from("G1"),
-///     registration: String::from("D-6507"),
-///     aircraft_type: String::from("ASK-21"),
-///     visible: true,
+///     competition_number: Some(String::from("G1")),
+///     registration: Some(String::from("D-6507")),
+///     model: Some(String::from("ASK-21")),
+///     ..Default::default()
 /// };
 ///
 /// let mapping = HashMap::from([(aircraft.id.clone(), aircraft.clone())]);
@@ -61,112 +112,116 @@ static LINE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(LINE_PATTERN).u
 /// let line = "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 id0AAB1234 -019fpm +0.0rot 5.5dB 3e -4.3kHz";
 ///
 /// let result = convert(line, &mapping);
-/// assert!(result.is_some());
+/// assert!(result.is_ok());
 /// assert_eq!(result.unwrap().aircraft.id, aircraft.id);
 /// ```
-pub fn convert(line: &str, aircraft: &HashMap<AircraftId, Aircraft>) -> Option<Status> {
-    let Some(captures) = LINE_REGEX.captures(line) else {
-        debug!("Line not parseable");
-        return None;
+pub fn convert(line: &str, aircraft: &HashMap<AircraftId, Aircraft>) -> Result<Status, ParseError> {
+    convert_packet(parse_packet(line)?, aircraft)
+}
+
+/// Tries converting an already parsed APRS packet into a `Status`, see `convert`
+///
+/// # Arguments
+///
+/// * `packet` - The parsed APRS line of the APRS server
+/// * `aircraft` - Mapping of `AircraftId` => `Aircraft`, necessary for conversion
+pub fn convert_packet(
+    packet: Packet,
+    aircraft: &HashMap<AircraftId, Aircraft>,
+) -> Result<Status, ParseError> {
+    let Body::Position(report) = packet.body else {
+        return Err(ParseError::UnsupportedPacketType);
     };
 
-    let id = captures.name("id")?.as_str();
+    let (source, parse_identity) = Source::by_destination(&packet.header.destination);
+    let identity = parse_identity(&report.comment, &packet.header)?;
+    let id = identity.id.as_str();
+
+    let model = identity
+        .aircraft_type
+        .map(|t| t.get_name())
+        .map(String::from);
 
-    let aircraft = if let Some(a) = aircraft.get(id) {
+    let aircraft = if let Some(a) = find_aircraft(aircraft, id, identity.address_type) {
         if a.model.is_some() {
             a.clone()
         } else {
-            let model = get_aircraft_type_by_capture(&captures, "type")
-                .map(|t| t.get_name())
-                .map(String::from);
-
             a.with_model(model)
         }
     } else {
         debug!("Unknown aircraft id '{id}'");
 
-        let model = get_aircraft_type_by_capture(&captures, "type")
-            .map(|t| t.get_name())
-            .map(String::from);
-
         Aircraft {
             id: String::from(id),
-            call_sign: None,
-            registration: None,
             model,
-            visible: true,
+            ..Default::default()
         }
     };
 
+    let comment = &report.comment;
+    let time_stamp = get_current_timestamp();
+
     let status = Status {
+        header: packet.header,
+        source,
         aircraft,
+        address_type: identity.address_type,
         position: Position {
-            latitude: capture_as_coordinate_value(&captures, "latitude")?,
-            longitude: capture_as_coordinate_value(&captures, "longitude")?,
+            latitude: report.latitude,
+            longitude: report.longitude,
         },
-        speed: capture_as_u16(&captures, "speed", FACTOR_KNOTS_TO_KM_H),
-        vertical_speed: capture_as_f32(&captures, "verticalSpeed", FACTOR_FT_MIN_TO_M_SEC),
-        altitude: capture_as_u16(&captures, "altitude", FACTOR_FT_TO_M),
-        turn_rate: capture_as_f32(&captures, "turnRate", FACTOR_TURNS_TWO_MIN_TO_TURNS_MIN),
-        course: capture_as_u16(&captures, "course", 1.0),
-        time_stamp: get_current_timestamp(),
+        speed: report
+            .speed
+            .and_then(|s| convert_as_u16(s, FACTOR_KNOTS_TO_KM_H)),
+        vertical_speed: comment.vertical_speed.map(|v| v * FACTOR_FT_MIN_TO_M_SEC),
+        altitude: report
+            .altitude
+            .and_then(|a| convert_as_i32(a, FACTOR_FT_TO_M)),
+        turn_rate: comment
+            .turn_rate
+            .map(|t| t * FACTOR_TURNS_TWO_MIN_TO_TURNS_MIN),
+        course: report.course,
+        signal_quality: comment.signal_quality,
+        bit_errors: comment.bit_errors,
+        frequency_offset: comment.frequency_offset,
+        gps_horizontal_accuracy: comment.gps_accuracy.map(|(h, _)| h),
+        gps_vertical_accuracy: comment.gps_accuracy.map(|(_, v)| v),
+        ground_state: if source == Source::Fanet {
+            get_ground_state(&comment.text)
+        } else {
+            None
+        },
+        packet_time_stamp: report
+            .time_of_day
+            .map(|time_of_day| get_timestamp_by_time_of_day(time_of_day, time_stamp)),
+        time_stamp,
     };
 
-    Some(status)
+    Ok(status)
 }
 
-/// Tries converting a `Captures` value to `f32` and multiply it to a `conversion_factor`
+/// Converts a `u16` value by multiplying it with a `conversion_factor`
 ///
 /// # Arguments
 ///
-/// * `captures` - The regex `Captures` to look up
-/// * `name` - Name of the captured value that should be converted
+/// * `value` - The value that should be converted
 /// * `conversion_factor` - The factor that the value should be multiplied with
 ///
 /// # Examples
 ///
-/// ```
-/// let captures = Regex::new(r"(?<value>[\d.]+)")
-///     .unwrap()
-///     .captures("12.34")
-///     .unwrap();
-///
-/// assert!(capture_as_f32(&captures, "value", 1.0).is_some_and(|f| f == 12.34));
-/// ```
-fn capture_as_f32(captures: &Captures, name: &str, conversion_factor: f32) -> Option<f32> {
-    let string_value = captures.name(name)?.as_str();
-    let value = string_value.parse::<f32>().ok()?;
-
-    Some(value * conversion_factor)
-}
-
-/// Tries converting a `Captures` value to `u16` and multiply it to a `conversion_factor`
-///
-/// # Arguments
-///
-/// * `captures` - The regex `Captures` to look up
-/// * `name` - Name of the captured value that should be converted
-/// * `conversion_factor` - The factor that the value should be multiplied with
-///
-/// # Examples
-///
-/// ```
-/// let captures = Regex::new(r"(?<value>\d+)")
-///     .unwrap()
-///     .captures("1234")
-///     .unwrap();
-///
-/// assert!(capture_as_u16(&captures, "value", 1.0).is_some_and(|f| f == 1234));
+/// ```ignore
+/// assert_eq!(convert_as_u16(1234, 2.0), Some(2468));
+/// assert_eq!(convert_as_u16(7, 1.852), Some(13));
 /// ```
 /// # Notes
 ///
-/// Returns `None` if _value_ * `conversion_factor` would under- / overflow `u16` ranges
-fn capture_as_u16(captures: &Captures, name: &str, conversion_factor: f32) -> Option<u16> {
-    let string_value = captures.name(name)?.as_str();
-    let value = string_value.parse::<u16>().ok()?;
-    let converted_value = f32::from(value) * conversion_factor;
+/// Returns `None` if `value` * `conversion_factor` would under- / overflow `u16` ranges. The
+/// result is rounded to the nearest integer (halfway cases away from zero), like the one of
+/// `convert_as_i32`.
+fn convert_as_u16(value: u16, conversion_factor: f64) -> Option<u16> {
+    let converted_value = (f64::from(value) * conversion_factor).round();
 
-    if converted_value < f32::from(u16::MIN) || converted_value > f32::from(u16::MAX) {
+    if converted_value < f64::from(u16::MIN) || converted_value > f64::from(u16::MAX) {
         return None;
     }
 
@@ -176,156 +231,135 @@ fn capture_as_u16(captures: &Captures, name: &str, conversion_factor: f32) -> Op
     Some(converted_value as u16)
 }
 
-/// Tries converting a `Captures` APRS coordinate value to a latitude / longitude value as `f32`
+/// Converts an `i32` value by multiplying it with a `conversion_factor`
 ///
 /// # Arguments
 ///
-/// * `captures` - The regex `Captures` to look up
-/// * `name` - Name of the captured value that should be converted
+/// * `value` - The value that should be converted
+/// * `conversion_factor` - The factor that the value should be multiplied with
 ///
 /// # Examples
 ///
+/// ```ignore
+/// assert_eq!(convert_as_i32(-1312, 0.3048), Some(-400));
 /// ```
-/// let captures = Regex::new(r"(?<value>.+)")
-///     .unwrap()
-///     .captures("1029.35S")
-///     .unwrap();
+/// # Notes
 ///
-/// assert!(capture_as_coordinate_value(&captures, "value").is_some_and(|f| f == -10.489166));
-/// ```
-fn capture_as_coordinate_value(captures: &Captures, name: &str) -> Option<f32> {
-    /* Latitude and longitude (by APRS-standard) are given as following: ddmm.mmD where d = "degree",
-     * m = "minute" and D = "direction".
-     * Notice that minutes are decimals, so 0.5 minutes equal 0 minutes, 30 secs.
-     * We'll separate degrees and minutes, so we can convert it to a "degree"-only value. */
-
-    let string_value = captures.name(name)?.as_str();
-    let aprs_value = string_value
-        .chars()
-        .filter(char::is_ascii_digit)
-        .collect::<String>()
-        .parse::<f32>()
-        .ok()?;
-
-    let orientation = string_value.chars().last()?; /* "N", "E", "S" or "W" */
-
-    let degrees = f32::floor(aprs_value / 1_0000.0); // Separating   "dd" from "ddmmmm"
-    let minutes = f32::floor(aprs_value % 1_0000.0) // Separating "mmmm" from "ddmmmm"
-        / 60.0 // because 60 minutes = 1 degree
-        / 100.0; // because of the removed decimal separator
-
-    if (f64::from(degrees) + f64::from(minutes)) > f64::from(f32::MAX) {
-        /* Don't think that's possible but just to be sure */
+/// Returns `None` if `value` * `conversion_factor` would under- / overflow `i32` ranges. The
+/// result is rounded to the nearest integer (halfway cases away from zero), like the one of
+/// `convert_as_u16`.
+pub(crate) fn convert_as_i32(value: i32, conversion_factor: f64) -> Option<i32> {
+    let converted_value = (f64::from(value) * conversion_factor).round();
+
+    if converted_value < f64::from(i32::MIN) || converted_value > f64::from(i32::MAX) {
         return None;
     }
 
-    let value = degrees + minutes;
-
-    if orientation == 'S' || orientation == 'W' {
-        Some(value * -1.0)
-    } else {
-        Some(value)
-    }
+    /* We check for range, so truncation can't happen. */
+    #[allow(clippy::cast_possible_truncation)]
+    Some(converted_value as i32)
 }
 
-/// Tries extracting the aircraft type from a `Capture` of the first encoded id field
+/// Decodes the id details of the aircraft id field ("XX" of "idXXYYYYYY")
 ///
 /// # Arguments
 ///
-/// * `captures` - The regex `Captures` to look up
-/// * `name` - Name of the captured value that should be converted
-///
-/// # Examples
+/// * `value` - The encoded id details
 ///
-/// ```
-/// let capture = Regex::new(r"(?<value>.*)")
-///     .unwrap()
-///     .captures("2D")
-///     .unwrap();
+/// # Returns
 ///
-/// let aircraft_type = et_aircraft_type_by_capture(&capture, "value");
+/// The decoded `IdDetails` or a `ParseError` if the aircraft has stealth- or no-tracking-mode
+/// active.
 ///
-/// assert!(aircraft_typ.is_some());
-/// assert!(aircraft_type.unwrap() == AircraftType::Balloon);
-/// ```
+/// # Examples
 ///
-/// # References
+/// ```ignore
+/// let id_details = get_id_details(0x2D);
 ///
-/// - [OGN Wiki](http://wiki.glidernet.org/wiki:ogn-flavoured-aprs#toc2)
-fn get_aircraft_type_by_capture(captures: &Captures, name: &str) -> Option<AircraftType> {
-    let string_value = captures.name(name)?.as_str();
-    let value = u8::from_str_radix(string_value, 16).ok()?;
-
-    /* Aircraft id field is built like this: "id" + "XX" + "YYYYYY" where the last part is the
-     * aircraft identifier (that matches DDB). "XX" is a 2-digit hex value built like this:
-     * 0bSTttttaa where "S" indicates stealth mode (should *NEVER* be sent over OGN), "T" is
-     * no-tracking mode (should *NEVER* be parsed by above_me), "tttt" is the aircraft type
-     * and "aa" is the address type. As we only need "tttt", we shift the whole number two
-     * digits to the left and just care for the lowest four bytes. */
-
-    /* This should *NEVER* happen! */
-    assert_eq!(
-        value & 0b1000_0000,
-        0,
-        "Line to be parsed has stealth mode active"
-    );
-
-    /* This should *NEVER* happen! */
-    assert_eq!(
-        value & 0b0100_0000,
-        0,
-        "Line to be parsed has no-tracking mode active"
-    );
-
-    let aircraft_type_value = (value >> 2) & 0b1111;
-    AircraftType::from_aprs_u8(aircraft_type_value)
+/// assert!(id_details.is_ok_and(|d| d.aircraft_type == Some(AircraftType::Balloon)));
+/// ```
+pub(super) fn get_id_details(value: u8) -> Result<IdDetails, ParseError> {
+    let id_details = IdDetails::from_u8(value);
+
+    if id_details.stealth {
+        return Err(ParseError::Stealth);
+    }
+
+    if id_details.no_tracking {
+        return Err(ParseError::NoTracking);
+    }
+
+    Ok(id_details)
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::ogn::{aprs::GroundState, MetadataSource};
+
+    use proptest::{collection::vec, prelude::*};
+
     use super::*;
 
     #[test]
-    fn get_type_works() {
-        let regex = Regex::new(r"(?<value>.*)").unwrap();
+    fn id_details_decodes_correctly() {
+        assert_eq!(
+            IdDetails::from_u8(0x2D),
+            IdDetails {
+                stealth: false,
+                no_tracking: false,
+                aircraft_type: Some(AircraftType::Balloon),
+                address_type: AddressType::Icao,
+            }
+        );
 
-        assert!(
-            get_aircraft_type_by_capture(&regex.captures("2D").unwrap(), "value")
-                .is_some_and(|t| t == AircraftType::Balloon)
+        assert_eq!(
+            IdDetails::from_u8(0xC6),
+            IdDetails {
+                stealth: true,
+                no_tracking: true,
+                aircraft_type: Some(AircraftType::Glider),
+                address_type: AddressType::Flarm,
+            }
         );
 
-        assert!(
-            get_aircraft_type_by_capture(&regex.captures("07").unwrap(), "value")
-                .is_some_and(|t| t == AircraftType::Glider)
+        assert_eq!(
+            IdDetails::from_u8(0x00),
+            IdDetails {
+                stealth: false,
+                no_tracking: false,
+                aircraft_type: None,
+                address_type: AddressType::Random,
+            }
         );
+    }
 
-        assert!(get_aircraft_type_by_capture(&regex.captures("00").unwrap(), "XXX").is_none());
-        assert!(get_aircraft_type_by_capture(&regex.captures("ZZZZ").unwrap(), "value").is_none());
-        assert!(get_aircraft_type_by_capture(&regex.captures("00").unwrap(), "value").is_none());
+    #[test]
+    fn get_id_details_works() {
+        assert!(get_id_details(0x2D).is_ok_and(|d| d.aircraft_type == Some(AircraftType::Balloon)));
+        assert!(get_id_details(0x07).is_ok_and(|d| d.aircraft_type == Some(AircraftType::Glider)));
+        assert!(get_id_details(0x00).is_ok_and(|d| d.aircraft_type.is_none()));
     }
 
     #[test]
-    #[should_panic]
-    fn get_type_panics_on_stealth_mode() {
-        let regex = Regex::new(r"(?<value>.*)").unwrap();
-        get_aircraft_type_by_capture(&regex.captures("80").unwrap(), "value");
+    fn get_id_details_rejects_stealth_mode() {
+        for value in [0x80, 0xBF, 0xC0, 0xFF] {
+            assert!(get_id_details(value).is_err_and(|e| e == ParseError::Stealth));
+        }
     }
 
     #[test]
-    #[should_panic]
-    fn get_type_panics_on_no_tracking_mode() {
-        let regex = Regex::new(r"(?<value>.*)").unwrap();
-        get_aircraft_type_by_capture(&regex.captures("40").unwrap(), "value");
+    fn get_id_details_rejects_no_tracking_mode() {
+        for value in [0x40, 0x7F] {
+            assert!(get_id_details(value).is_err_and(|e| e == ParseError::NoTracking));
+        }
     }
 
     #[test]
     fn convert_works() {
         let valid_aircraft = Aircraft {
             id: String::from("AB1234"),
-            call_sign: None,
-            registration: None,
-            model: None,
-            visible: true,
+            metadata_source: MetadataSource::Ddb,
+            ..Default::default()
         };
 
         let mapping = HashMap::from([(valid_aircraft.id.clone(), valid_aircraft.clone())]);
@@ -334,8 +368,8 @@ mod tests {
             (
                 "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 id0AAB1234 -019fpm +0.0rot 5.5dB 3e -4.3kHz",
                 valid_aircraft.id.as_str(),
-                51.188667,
-                Some(12),
+                51.18866666666666,
+                Some(13),
                 Some(-0.09652),
                 Some(185),
                 Some(0.0),
@@ -344,8 +378,8 @@ mod tests {
             (
                 "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N\\00102.04W'086/007/A=000607 id0AAB1234 5.5dB 3e -4.3kHz",
                 valid_aircraft.id.as_str(),
-                51.188667,
-                Some(12),
+                51.18866666666666,
+                Some(13),
                 None,
                 Some(185),
                 None,
@@ -354,7 +388,7 @@ mod tests {
             (
                 "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N\\00102.04W' id0AAB1234 5.5dB 3e -4.3kHz",
                 valid_aircraft.id.as_str(),
-                51.188667,
+                51.18866666666666,
                 None,
                 None,
                 None,
@@ -364,7 +398,7 @@ mod tests {
             (
                 "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W' id0AAB1234 -019fpm +0.0rot 5.5dB 3e -4.3kHz",
                 valid_aircraft.id.as_str(),
-                51.188667,
+                51.18866666666666,
                 None,
                 Some(-0.09652),
                 None,
@@ -374,8 +408,8 @@ mod tests {
             (
                 "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 id0AAB1234 +0.0rot 5.5dB 3e -4.3kHz",
                 valid_aircraft.id.as_str(),
-                51.188667,
-                Some(12),
+                51.18866666666666,
+                Some(13),
                 None,
                 Some(185),
                 Some(0.0),
@@ -384,7 +418,7 @@ mod tests {
             (
                 "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W' id0AAB1234 +0.0rot 5.5dB 3e -4.3kHz",
                 valid_aircraft.id.as_str(),
-                51.188667,
+                51.18866666666666,
                 None,
                 None,
                 None,
@@ -397,20 +431,59 @@ mod tests {
             data_set
         {
             let result = convert(line, &mapping);
-            assert!(result.is_some());
+            assert!(result.is_ok());
 
             let status = result.unwrap();
             assert_eq!(&status.aircraft.id, aircraft_id);
+            assert_eq!(status.header.source, "FLRDDE626");
+            assert_eq!(status.header.receiver.as_deref(), Some("EGHL"));
+            assert_eq!(status.source, Source::Flarm);
+            assert_eq!(status.address_type, AddressType::Flarm);
             assert_eq!(&status.position.latitude, latitude);
             assert_eq!(&status.speed, speed);
             assert_eq!(&status.vertical_speed, vertical_speed);
             assert_eq!(&status.altitude, altitude);
             assert_eq!(&status.turn_rate, turn_rate);
             assert_eq!(&status.course, course);
+            assert!(status
+                .packet_time_stamp
+                .is_some_and(|t| t % 86_400 == 27_948));
             assert!(status.time_stamp > 0);
         }
     }
 
+    #[test]
+    fn convert_checks_address_type_of_aircraft() {
+        let mapping = HashMap::from([(
+            String::from("DDE626"),
+            Aircraft {
+                id: String::from("DDE626"),
+                competition_number: Some(String::from("G1")),
+                registration: Some(String::from("D-6507")),
+                model: Some(String::from("ASK-21")),
+                device_type: Some(AddressType::Flarm),
+                metadata_source: MetadataSource::Ddb,
+                visible: false,
+                ..Default::default()
+            },
+        )]);
+
+        let flarm = convert("FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 id0ADDE626 -019fpm", &mapping).unwrap();
+
+        assert_eq!(flarm.aircraft.registration.as_deref(), Some("D-6507"));
+        assert!(!flarm.aircraft.visible);
+
+        /* Same id, but an ICAO address, so it's another aircraft */
+        let icao = convert("ICADDE626>OGADSB,qAS,EGHL:/074548h5111.32N\\00102.04W^086/007/A=000607 id25DDE626 -019fpm", &mapping).unwrap();
+
+        assert_eq!(icao.address_type, AddressType::Icao);
+        assert_eq!(icao.aircraft.competition_number, None);
+        assert_eq!(icao.aircraft.registration, None);
+        assert_eq!(icao.aircraft.model.as_deref(), Some("Jet"));
+        assert_eq!(icao.aircraft.metadata_source, MetadataSource::Aprs);
+        assert!(icao.aircraft.visible);
+    }
+
     #[test]
     fn convert_works_with_unknown_aircraft() {
         let mapping = HashMap::new();
@@ -418,11 +491,11 @@ mod tests {
         let line = "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 id0AAB1234 -019fpm +0.0rot 5.5dB 3e -4.3kHz";
 
         let result = convert(line, &mapping);
-        assert!(result.is_some());
+        assert!(result.is_ok());
 
         let status = result.unwrap();
         assert_eq!(status.aircraft.id, "AB1234");
-        assert!(status.aircraft.call_sign.is_none());
+        assert!(status.aircraft.competition_number.is_none());
         assert!(status.aircraft.registration.is_none());
         assert!(status.aircraft.model.is_some_and(|v| v == "Tow plane"));
         assert!(status.aircraft.visible);
@@ -432,67 +505,316 @@ mod tests {
     fn convert_ignores_stealth_mode() {
         let mapping = HashMap::new();
         let line = "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 id8AAB1234 -019fpm +0.0rot 5.5dB 3e -4.3kHz";
-        assert!(convert(line, &mapping).is_none());
+        assert!(convert(line, &mapping).is_err_and(|e| e == ParseError::Stealth));
     }
 
     #[test]
     fn convert_ignores_no_tracking_mode() {
         let mapping = HashMap::new();
         let line = "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 id4AAB1234 -019fpm +0.0rot 5.5dB 3e -4.3kHz";
-        assert!(convert(line, &mapping).is_none());
+        assert!(convert(line, &mapping).is_err_and(|e| e == ParseError::NoTracking));
     }
 
     #[test]
-    fn test_capture_as_f32_works() {
-        let captures = Regex::new(r"(?<value>[\d.]+)")
-            .unwrap()
-            .captures("12.34")
-            .unwrap();
-
-        assert!(capture_as_f32(&captures, "value", 1.0).is_some_and(|f| f == 12.34));
-        assert!(capture_as_f32(&captures, "value", 2.0).is_some_and(|f| f == 24.68));
+    fn convert_works_with_other_sources() {
+        let mapping = HashMap::new();
+
+        let data_set = &[
+            (
+                "ICA3D17F2>OGADSB,qAS,dl4mea:/074549h4821.61N\\01224.49E^322/444/A=012369 !W81! id253D17F2 -0384fpm FL123.35 A3:AFR123",
+                Source::Adsb,
+                "3D17F2",
+                AddressType::Icao,
+                Some(-1.9507201),
+            ),
+            (
+                "PAW404CB8>OGPAW,qAS,UKBSO:/094045h5146.42N/00111.33W'000/000/A=000000 !W26! id1C404CB8 10.8dB +1.4kHz",
+                Source::PilotAware,
+                "404CB8",
+                AddressType::Random,
+                None,
+            ),
+            (
+                "FNT11075F>OGNFNT,qAS,Kraehbrg:/203010h4742.10N/00820.21E'000/000/A=001591 !W77! id1E11075F +000fpm",
+                Source::Fanet,
+                "11075F",
+                AddressType::Flarm,
+                Some(0.0),
+            ),
+            (
+                "SKY3E5906>OGNSKY,qAS,SafeSky:/072553h5103.47N/00524.81E'065/031/A=001250 !W05! id1C3E5906 +010fpm gps6x1",
+                Source::SafeSky,
+                "3E5906",
+                AddressType::Random,
+                Some(0.0508),
+            ),
+            (
+                "ICA3E7540>OGSPOT,qAS,SPOT:/161427h1448.35S/04610.86W'000/000/A=008677 id0-2860357 SPOT3 GOOD",
+                Source::Spot,
+                "3E7540",
+                AddressType::Icao,
+                None,
+            ),
+            (
+                "FLRDDDD33>OGSKYL,qAS,SKYLINES:/134403h4225.90N/00144.83E'000/000/A=008438 id2816 +000fpm",
+                Source::Skylines,
+                "DDDD33",
+                AddressType::Flarm,
+                Some(0.0),
+            ),
+        ];
+
+        for (line, source, id, address_type, vertical_speed) in data_set {
+            let result = convert(line, &mapping);
+            assert!(result.is_ok());
+
+            let status = result.unwrap();
+            assert_eq!(&status.source, source);
+            assert_eq!(status.aircraft.id, *id);
+            assert_eq!(&status.address_type, address_type);
+            assert_eq!(&status.vertical_speed, vertical_speed);
+        }
+    }
+
+    #[test]
+    fn convert_works_with_compressed_positions() {
+        let mapping = HashMap::new();
+
+        for (compressed, uncompressed) in [
+            (
+                "FLRDDE626>APRS,qAS,EGHL:/074548h/5L!!<*e7>7P[ id0ADDE626 -019fpm +0.0rot",
+                "FLRDDE626>APRS,qAS,EGHL:/074548h4930.00N/07245.00W>088/036 id0ADDE626 -019fpm +0.0rot",
+            ),
+            (
+                "FLRDDE626>APRS,qAS,EGHL:/074548h/5L!!<*e7>S]1 id0ADDE626 5.5dB",
+                "FLRDDE626>APRS,qAS,EGHL:/074548h4930.00N/07245.00W>/A=010004 id0ADDE626 5.5dB",
+            ),
+        ] {
+            let compressed = convert(compressed, &mapping).unwrap();
+            let uncompressed = convert(uncompressed, &mapping).unwrap();
+
+            assert!((compressed.position.latitude - uncompressed.position.latitude).abs() < 1e-4);
+            assert!(
+                (compressed.position.longitude - uncompressed.position.longitude).abs() < 1e-4
+            );
+            assert_eq!(compressed.header, uncompressed.header);
+            assert_eq!(compressed.aircraft.id, uncompressed.aircraft.id);
+            assert_eq!(compressed.address_type, uncompressed.address_type);
+            assert_eq!(compressed.course, uncompressed.course);
+            assert_eq!(compressed.speed, uncompressed.speed);
+            assert_eq!(compressed.altitude, uncompressed.altitude);
+            assert_eq!(compressed.vertical_speed, uncompressed.vertical_speed);
+            assert_eq!(compressed.turn_rate, uncompressed.turn_rate);
+            assert_eq!(compressed.signal_quality, uncompressed.signal_quality);
+            assert_eq!(compressed.packet_time_stamp, uncompressed.packet_time_stamp);
+        }
+    }
+
+    #[test]
+    fn convert_parses_fanet_ground_state() {
+        let mapping = HashMap::new();
+
+        let result = convert(
+            "FNT1103CE>OGNFNT,qAS,LFNF:/161045h4345.80N/00537.87E_000/000/A=001234 id1E1103CE Need a ride",
+            &mapping,
+        );
+        assert!(result.is_ok_and(|s| s.ground_state == Some(GroundState::NeedRide)));
+
+        /* Ground states are only sent by FANET */
+        let result = convert(
+            "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 id0AAB1234 Landed",
+            &mapping,
+        );
+        assert!(result.is_ok_and(|s| s.ground_state.is_none()));
+    }
+
+    #[test]
+    fn convert_parses_signal_quality() {
+        let mapping = HashMap::new();
+
+        let data_set = &[
+            (
+                "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 id0AAB1234 -019fpm +0.0rot 5.5dB 3e -4.3kHz gps2x3",
+                Some(5.5),
+                Some(3),
+                Some(-4.3),
+                Some(2),
+                Some(3),
+            ),
+            (
+                "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 id0AAB1234 -019fpm +0.0rot FL012.34 37.2dB 0e +1.2kHz",
+                Some(37.2),
+                Some(0),
+                Some(1.2),
+                None,
+                None,
+            ),
+            (
+                "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 id0AAB1234 -019fpm +0.0rot",
+                None,
+                None,
+                None,
+                None,
+                None,
+            ),
+        ];
+
+        for (
+            line,
+            signal_quality,
+            bit_errors,
+            frequency_offset,
+            gps_horizontal_accuracy,
+            gps_vertical_accuracy,
+        ) in data_set
+        {
+            let result = convert(line, &mapping);
+            assert!(result.is_ok());
+
+            let status = result.unwrap();
+            assert_eq!(&status.signal_quality, signal_quality);
+            assert_eq!(&status.bit_errors, bit_errors);
+            assert_eq!(&status.frequency_offset, frequency_offset);
+            assert_eq!(&status.gps_horizontal_accuracy, gps_horizontal_accuracy);
+            assert_eq!(&status.gps_vertical_accuracy, gps_vertical_accuracy);
+        }
+    }
+
+    #[test]
+    fn convert_returns_parse_errors() {
+        let mapping = HashMap::new();
+
+        let data_set = &[
+            ("FLRDDE626>APRS,qAS,EGHL", ParseError::Malformed),
+            (
+                "EGHL>OGNSDR,TCPIP*,qAC,GLIDERN2:>074555h v0.2.7.RPI-GPU CPU:0.7",
+                ParseError::UnsupportedPacketType,
+            ),
+            (
+                "FLRDDE626>APRS,qAS,EGHL:/074548h id0AAB1234 -019fpm +0.0rot",
+                ParseError::NoPosition,
+            ),
+            (
+                "FLRDDE626>APRS,qAS,EGHL:/074548h5160.32N/00102.04W'086/007/A=000607 id0AAB1234",
+                ParseError::BadCoordinate,
+            ),
+            (
+                "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 -019fpm",
+                ParseError::UnknownIdFormat,
+            ),
+            (
+                "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 idC2AB1234",
+                ParseError::Stealth,
+            ),
+            (
+                "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 id7FAB1234",
+                ParseError::NoTracking,
+            ),
+        ];
+
+        for (line, parse_error) in data_set {
+            assert!(convert(line, &mapping).is_err_and(|e| e == *parse_error));
+        }
+    }
+
+    #[test]
+    fn convert_as_u16_works() {
+        assert_eq!(convert_as_u16(1234, 1.0), Some(1234));
+        assert_eq!(convert_as_u16(1234, 2.0), Some(2468));
+        assert_eq!(convert_as_u16(7, 1.852), Some(13));
+        assert_eq!(convert_as_u16(5, 0.5), Some(3));
+    }
+
+    #[test]
+    fn convert_as_u16_fails_on_out_of_range() {
+        assert!(convert_as_u16(1234, 10.0).is_some());
+        assert!(convert_as_u16(1234, 100.0).is_none());
+        assert!(convert_as_u16(1234, -1.0).is_none());
     }
 
     #[test]
-    fn test_capture_as_u16_works() {
-        let captures = Regex::new(r"(?<value>\d+)")
-            .unwrap()
-            .captures("1234")
-            .unwrap();
-
-        assert!(capture_as_u16(&captures, "value", 1.0).is_some_and(|f| f == 1234));
-        assert!(capture_as_u16(&captures, "value", 2.0).is_some_and(|f| f == 2468));
+    fn convert_as_i32_works() {
+        assert_eq!(convert_as_i32(-12, 1.0), Some(-12));
+        assert_eq!(convert_as_i32(-12, 0.3048), Some(-4));
+        assert_eq!(convert_as_i32(-5, 0.5), Some(-3));
     }
 
     #[test]
-    fn test_capture_as_u16_fails_on_out_of_range() {
-        let captures = Regex::new(r"(?<value>\d+)")
-            .unwrap()
-            .captures("1234")
-            .unwrap();
-
-        assert!(capture_as_u16(&captures, "value", 10.0).is_some());
-        assert!(capture_as_u16(&captures, "value", 100.0).is_none());
-        assert!(capture_as_u16(&captures, "value", -1.0).is_none());
+    fn convert_as_i32_handles_values_beyond_u16() {
+        assert_eq!(convert_as_i32(250_000, 1.0), Some(250_000));
+        assert_eq!(convert_as_i32(250_000, 100_000.0), None);
     }
 
     #[test]
-    fn test_capture_as_coordinate_value_works() {
-        let captures = Regex::new(r"(?<value>.+)")
-            .unwrap()
-            .captures("5111.32N")
-            .unwrap();
+    fn convert_parses_negative_altitude() {
+        let mapping = HashMap::new();
+        let line = "FLRDDE626>APRS,qAS,EGHL:/074548h3130.00N/03530.00E'086/007/A=-01312 id0AAB1234 -019fpm +0.0rot";
 
-        assert!(capture_as_coordinate_value(&captures, "value").is_some_and(|f| f == 51.188667));
+        let result = convert(line, &mapping);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().altitude, Some(-400));
     }
 
     #[test]
-    fn test_capture_as_coordinate_value_works_on_negative() {
-        let captures = Regex::new(r"(?<value>.+)")
-            .unwrap()
-            .captures("1029.35S")
-            .unwrap();
+    fn convert_applies_precision_enhancement() {
+        let mapping = HashMap::new();
+        let line = "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 !W12! id0AAB1234 -019fpm +0.0rot 5.5dB 3e -4.3kHz";
+
+        let result = convert(line, &mapping);
+        assert!(result.is_ok());
+
+        let status = result.unwrap();
+        assert_eq!(status.position.latitude, 51.18868333333333);
+        assert_eq!(status.position.longitude, -1.0340333333333334);
+    }
+
+    proptest! {
+        #[test]
+        fn convert_never_panics_on_arbitrary_bytes(bytes in vec(any::<u8>(), 0..256)) {
+            let _ = convert(&String::from_utf8_lossy(&bytes), &HashMap::new());
+        }
+
+        #[test]
+        fn convert_round_trips_generated_lines(
+            course in 0u16..=360,
+            speed in 0u16..=999,
+            altitude in -99_999i32..=999_999,
+            vertical_speed in -9_999i16..=9_999,
+            details in 0u8..0x40,
+            id in "[0-9A-F]{6}",
+        ) {
+            let line = format!(
+                "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'{course:03}/{speed:03}/A={altitude:06} id{details:02X}{id} {vertical_speed:+04}fpm"
+            );
+
+            let status = convert(&line, &HashMap::new());
+            prop_assert!(status.is_ok(), "{}", line);
+
+            let status = status.unwrap();
+            prop_assert_eq!(status.aircraft.id, id);
+            prop_assert_eq!(status.address_type, AddressType::from_aprs_u8(details & MASK_ADDRESS_TYPE));
+            prop_assert_eq!(status.course, Some(course));
+            /* 1 kn = 1.852 km/h and 1 ft = 0.3048 m by definition */
+            prop_assert_eq!(
+                status.speed.map(i64::from),
+                Some(scale_rounded(i64::from(speed), 1852, 1000))
+            );
+            prop_assert_eq!(
+                status.altitude.map(i64::from),
+                Some(scale_rounded(i64::from(altitude), 3048, 10_000))
+            );
+
+            let vertical_speed = f64::from(vertical_speed) * 0.3048 / 60.0;
+            prop_assert!(status
+                .vertical_speed
+                .is_some_and(|v| (f64::from(v) - vertical_speed).abs() < 1e-4));
+        }
+    }
 
-        assert!(capture_as_coordinate_value(&captures, "value").is_some_and(|f| f == -10.489166));
+    /// Multiplies `value` with `numerator` / `denominator` using integers only, rounding halfway
+    /// cases away from zero
+    fn scale_rounded(value: i64, numerator: i64, denominator: i64) -> i64 {
+        let scaled = value * numerator;
+        (scaled.abs() * 2 + denominator) / (2 * denominator) * scaled.signum()
     }
 }
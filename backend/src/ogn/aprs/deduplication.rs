@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use crate::time::get_current_timestamp;
+
+use super::status::{Key, Status, MAX_STATUS_AGE};
+
+/// Merges the states of multiple upstreams by only letting through states that are newer than the
+/// latest one of the same aircraft. Prevents duplicates (same packet received from multiple
+/// upstreams) and older packets of a lagging upstream.
+pub struct Deduplicator {
+    /// Latest packet timestamp and receive timestamp per aircraft
+    latest: HashMap<Key, (u64, u64)>,
+    /// Timestamp of last removal of outdated entries
+    last_cleanup: u64,
+}
+
+impl Deduplicator {
+    /// Creates a new `Deduplicator`
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let deduplicator = Deduplicator::create();
+    /// ```
+    pub fn create() -> Deduplicator {
+        Deduplicator {
+            latest: HashMap::new(),
+            last_cleanup: get_current_timestamp(),
+        }
+    }
+
+    /// Checks whether a status is newer than the latest one of the same aircraft and, if so,
+    /// remembers it as the latest one.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The incoming status
+    ///
+    /// # Returns
+    ///
+    /// `true` if the status should be passed on, `false` if it's a duplicate or outdated
+    ///
+    /// # Notes
+    ///
+    /// States without packet time can't be compared and are always passed on.
+    ///
+    /// # Examples
+    ///
+    /// * test `deduplication::accept_discards_duplicates`
+    /// * test `deduplication::accept_discards_older_states`
+    /// * test `deduplication::accept_handles_midnight`
+    pub fn accept(&mut self, status: &Status) -> bool {
+        let Some(packet_time_stamp) = status.packet_time_stamp else {
+            return true;
+        };
+
+        self.remove_outdated_entries(status.time_stamp);
+
+        let key = status.key();
+
+        if let Some(&(latest_packet_time_stamp, latest_time_stamp)) = self.latest.get(&key) {
+            if status.time_stamp.saturating_sub(latest_time_stamp) <= MAX_STATUS_AGE
+                && packet_time_stamp <= latest_packet_time_stamp
+            {
+                return false;
+            }
+        }
+
+        self.latest
+            .insert(key, (packet_time_stamp, status.time_stamp));
+
+        true
+    }
+
+    /// Removes entries that haven't been updated within `MAX_STATUS_AGE`. Runs at most once per
+    /// `MAX_STATUS_AGE` so that we don't iterate over all entries on every status.
+    ///
+    /// # Arguments
+    ///
+    /// * `current_timestamp` - The current timestamp
+    fn remove_outdated_entries(&mut self, current_timestamp: u64) {
+        if current_timestamp.saturating_sub(self.last_cleanup) < MAX_STATUS_AGE {
+            return;
+        }
+
+        self.last_cleanup = current_timestamp;
+        self.latest.retain(|_, &mut (_, time_stamp)| {
+            current_timestamp.saturating_sub(time_stamp) <= MAX_STATUS_AGE
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ogn::{AddressType, Aircraft},
+        time::get_timestamp_by_time_of_day,
+    };
+
+    use super::*;
+
+    fn create_status(aircraft_id: &str, packet_time_stamp: Option<u64>) -> Status {
+        create_status_with_address_type(aircraft_id, AddressType::Flarm, packet_time_stamp)
+    }
+
+    fn create_status_with_address_type(
+        aircraft_id: &str,
+        address_type: AddressType,
+        packet_time_stamp: Option<u64>,
+    ) -> Status {
+        Status {
+            aircraft: Aircraft {
+                id: String::from(aircraft_id),
+                ..Default::default()
+            },
+            address_type,
+            packet_time_stamp,
+            time_stamp: get_current_timestamp(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a status of a packet sent at `time_of_day` and received at `time_stamp`, deriving
+    /// the packet timestamp like `convert`
+    fn create_status_at(time_of_day: u32, time_stamp: u64) -> Status {
+        Status {
+            packet_time_stamp: Some(get_timestamp_by_time_of_day(time_of_day, time_stamp)),
+            time_stamp,
+            ..create_status("AB1234", None)
+        }
+    }
+
+    #[test]
+    fn accept_discards_duplicates() {
+        let mut sut = Deduplicator::create();
+
+        assert!(sut.accept(&create_status("AB1234", Some(100))));
+        assert!(!sut.accept(&create_status("AB1234", Some(100))));
+        assert!(sut.accept(&create_status("CD5678", Some(100))));
+        assert!(sut.accept(&create_status_with_address_type(
+            "AB1234",
+            AddressType::Icao,
+            Some(100)
+        )));
+    }
+
+    #[test]
+    fn accept_discards_older_states() {
+        let mut sut = Deduplicator::create();
+
+        assert!(sut.accept(&create_status("AB1234", Some(100))));
+        assert!(sut.accept(&create_status("AB1234", Some(102))));
+        assert!(!sut.accept(&create_status("AB1234", Some(101))));
+        assert!(sut.accept(&create_status("AB1234", Some(103))));
+    }
+
+    #[test]
+    fn accept_handles_midnight() {
+        let mut sut = Deduplicator::create();
+
+        /* 2024-06-02 00:00:01 */
+        let after_midnight = 1_717_286_401;
+
+        /* Packet of 23:59:59, received at 00:00:01 */
+        assert!(sut.accept(&create_status_at(86_399, after_midnight)));
+        assert!(sut.accept(&create_status_at(0, after_midnight)));
+        assert!(!sut.accept(&create_status_at(86_399, after_midnight)));
+        assert!(!sut.accept(&create_status_at(0, after_midnight + 1)));
+        assert!(sut.accept(&create_status_at(1, after_midnight + 1)));
+    }
+
+    #[test]
+    fn accept_handles_midnight_of_lagging_upstream() {
+        let mut sut = Deduplicator::create();
+
+        /* 2024-06-01 23:59:59 */
+        let before_midnight = 1_717_286_399;
+
+        assert!(sut.accept(&create_status_at(86_399, before_midnight)));
+        assert!(sut.accept(&create_status_at(1, before_midnight + 2)));
+
+        /* Same packet of 23:59:59, relayed by a lagging upstream after midnight */
+        assert!(!sut.accept(&create_status_at(86_399, before_midnight + 3)));
+    }
+
+    #[test]
+    fn accept_passes_states_without_packet_time() {
+        let mut sut = Deduplicator::create();
+
+        assert!(sut.accept(&create_status("AB1234", None)));
+        assert!(sut.accept(&create_status("AB1234", None)));
+    }
+}
@@ -0,0 +1,37 @@
+use std::fmt::{Display, Formatter, Result};
+
+use serde::Serialize;
+
+/// Enum of reasons why an APRS line could not be converted into a `Status`
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParseError {
+    /// Line doesn't follow the APRS format at all
+    Malformed,
+    /// Line is not a position report (e.g. a status message)
+    UnsupportedPacketType,
+    /// Position report doesn't contain a position
+    NoPosition,
+    /// Position contains an invalid coordinate
+    BadCoordinate,
+    /// Aircraft id field is missing or has an unknown format
+    UnknownIdFormat,
+    /// Aircraft has stealth mode active
+    Stealth,
+    /// Aircraft has no-tracking mode active
+    NoTracking,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Self::Malformed => write!(f, "Malformed line"),
+            Self::UnsupportedPacketType => write!(f, "Unsupported packet type"),
+            Self::NoPosition => write!(f, "No position"),
+            Self::BadCoordinate => write!(f, "Bad coordinate"),
+            Self::UnknownIdFormat => write!(f, "Unknown id format"),
+            Self::Stealth => write!(f, "Stealth mode active"),
+            Self::NoTracking => write!(f, "No-tracking mode active"),
+        }
+    }
+}
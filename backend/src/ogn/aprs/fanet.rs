@@ -0,0 +1,316 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        LazyLock, Mutex,
+    },
+};
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::ogn::{find_aircraft, Aircraft, AircraftId};
+
+use super::{
+    packet::{Body, Packet},
+    status::{Status, MAX_STATUS_AGE},
+};
+
+/// Destination call of FANET packets
+const DESTINATION_FANET: &str = "OGNFNT";
+/// Prefix of FANET source callsigns, followed by the aircraft id
+const PREFIX_FANET: &str = "FNT";
+
+/// Regex pattern to extract the name from the status text of a FANET name packet (e.g.
+/// `Name="Mario"`)
+const NAME_PATTERN: &str = r#"^Name="(?<name>[^"]+)""#;
+/// Regex pattern to extract the ground state from the comment of a FANET ground tracking packet
+const GROUND_STATE_PATTERN: &str = r"(?i)(?:^| )(?<groundState>walking|vehicle|bike|boat|need a ride|landed|need technical support|need medical help|distress call automatically|distress call)\b";
+
+static NAME_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(NAME_PATTERN).unwrap());
+static GROUND_STATE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(GROUND_STATE_PATTERN).unwrap());
+
+/// Names of FANET aircraft by their id, shared between all upstreams. Names expire after
+/// `MAX_STATUS_AGE`, so that names of aircraft that are gone neither pile up nor stay attached to
+/// their ids indefinitely.
+#[derive(Default)]
+pub struct Names {
+    /// Name and timestamp of receiving it per aircraft id
+    names: Mutex<HashMap<AircraftId, (String, u64)>>,
+    /// Timestamp of the last removal of expired names
+    last_eviction: AtomicU64,
+}
+
+impl Names {
+    /// Stores / updates the name of an aircraft. Expired names are removed every
+    /// `MAX_STATUS_AGE`.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Id of the aircraft
+    /// * `name` - Name of the aircraft
+    /// * `time_stamp` - Timestamp of receiving the name
+    ///
+    /// # Examples
+    ///
+    /// * test `fanet::names_expire`
+    pub fn insert(&self, id: AircraftId, name: String, time_stamp: u64) {
+        let mut names = self.names.lock().expect("Mutex was poisoned");
+
+        let last_eviction = self.last_eviction.load(Ordering::Relaxed);
+        if time_stamp.saturating_sub(last_eviction) > MAX_STATUS_AGE {
+            names.retain(|_, (_, t)| !is_expired(*t, time_stamp));
+            self.last_eviction.store(time_stamp, Ordering::Relaxed);
+        }
+
+        names.insert(id, (name, time_stamp));
+    }
+
+    /// Returns the name of an aircraft, if it's known and not expired
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Id of the aircraft
+    /// * `current_timestamp` - Timestamp to check the expiry against
+    ///
+    /// # Examples
+    ///
+    /// * test `fanet::names_expire`
+    pub fn get(&self, id: &AircraftId, current_timestamp: u64) -> Option<String> {
+        self.names
+            .lock()
+            .expect("Mutex was poisoned")
+            .get(id)
+            .filter(|(_, t)| !is_expired(*t, current_timestamp))
+            .map(|(name, _)| name.clone())
+    }
+}
+
+/// Checks whether a name received at `time_stamp` is expired
+///
+/// # Arguments
+///
+/// * `time_stamp` - Timestamp of receiving the name
+/// * `current_timestamp` - Timestamp to check the expiry against
+fn is_expired(time_stamp: u64, current_timestamp: u64) -> bool {
+    current_timestamp.saturating_sub(time_stamp) > MAX_STATUS_AGE
+}
+
+/// State of a FANET participant that is on the ground
+///
+/// # References
+///
+/// - [FANET protocol](https://github.com/3s1d/fanet-stm32/blob/master/Src/fanet/radio/protocol.txt)
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroundState {
+    Walking,
+    Vehicle,
+    Bike,
+    Boat,
+    NeedRide,
+    Landed,
+    NeedTechnicalSupport,
+    NeedMedicalHelp,
+    DistressCall,
+    DistressCallAutomatically,
+}
+
+impl GroundState {
+    /// Gets the ground state for its textual representation (case insensitive)
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Textual representation, e.g. "Need a ride"
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// assert_eq!(GroundState::from_name("Landed"), Some(GroundState::Landed));
+    /// ```
+    fn from_name(value: &str) -> Option<GroundState> {
+        match value.to_lowercase().as_str() {
+            "walking" => Some(Self::Walking),
+            "vehicle" => Some(Self::Vehicle),
+            "bike" => Some(Self::Bike),
+            "boat" => Some(Self::Boat),
+            "need a ride" => Some(Self::NeedRide),
+            "landed" => Some(Self::Landed),
+            "need technical support" => Some(Self::NeedTechnicalSupport),
+            "need medical help" => Some(Self::NeedMedicalHelp),
+            "distress call" => Some(Self::DistressCall),
+            "distress call automatically" => Some(Self::DistressCallAutomatically),
+            _ => None,
+        }
+    }
+}
+
+/// Tries converting a parsed APRS line into the name of a FANET aircraft
+///
+/// # Arguments
+///
+/// * `packet` - The parsed APRS line
+///
+/// # Returns
+///
+/// Id and name of the aircraft if the line is a FANET name packet, `None` otherwise
+///
+/// # Examples
+///
+/// ```ignore
+/// let line = "FNT11123F>OGNFNT,qAS,Huenenbg:>202135h Name=\"Mario\" 14.9dB -4.3kHz";
+/// let packet = parse_packet(line).unwrap();
+///
+/// assert_eq!(
+///     convert_name(&packet),
+///     Some((String::from("11123F"), String::from("Mario")))
+/// );
+/// ```
+pub fn convert_name(packet: &Packet) -> Option<(AircraftId, String)> {
+    if packet.header.destination != DESTINATION_FANET {
+        return None;
+    }
+
+    let id = packet.header.source.strip_prefix(PREFIX_FANET)?;
+    let Body::Status(status) = &packet.body else {
+        return None;
+    };
+    let captures = NAME_REGEX.captures(&status.text)?;
+
+    Some((String::from(id), String::from(&captures["name"])))
+}
+
+/// Tries extracting the ground state of a FANET ground tracking packet
+///
+/// # Arguments
+///
+/// * `comment` - The comment of the position report
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(get_ground_state("id1E1103CE Landed"), Some(GroundState::Landed));
+/// ```
+pub fn get_ground_state(comment: &str) -> Option<GroundState> {
+    let captures = GROUND_STATE_REGEX.captures(comment)?;
+    GroundState::from_name(&captures["groundState"])
+}
+
+/// Adds the FANET name to the aircraft of a status, if the aircraft has no DDB entry and the
+/// name hasn't expired at the time of receiving the status
+///
+/// # Arguments
+///
+/// * `status` - The status whose aircraft should get the name
+/// * `aircraft` - Mapping of `AircraftId` => `Aircraft` of the DDB
+/// * `names` - The known FANET names
+///
+/// # Examples
+///
+/// * test `fanet::apply_name_skips_ddb_aircraft`
+pub fn apply_name(status: &mut Status, aircraft: &HashMap<AircraftId, Aircraft>, names: &Names) {
+    if status.aircraft.name.is_some()
+        || find_aircraft(aircraft, &status.aircraft.id, status.address_type).is_some()
+    {
+        return;
+    }
+
+    status.aircraft.name = names.get(&status.aircraft.id, status.time_stamp);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ogn::{
+        aprs::{conversion::convert, packet::parse_packet},
+        MetadataSource,
+    };
+
+    use super::*;
+
+    #[test]
+    fn convert_name_works() {
+        for (line, expected) in [
+            (
+                "FNT11123F>OGNFNT,qAS,Huenenbg:>202135h Name=\"Mario\" 14.9dB -4.3kHz",
+                Some((String::from("11123F"), String::from("Mario"))),
+            ),
+            ("FNT11123F>OGNFNT,qAS,Huenenbg:>202135h Name=\"\"", None),
+            ("FLRDDE626>APRS,qAS,EGHL:>202135h Name=\"Mario\"", None),
+        ] {
+            assert_eq!(convert_name(&parse_packet(line).unwrap()), expected);
+        }
+    }
+
+    #[test]
+    fn get_ground_state_works() {
+        for (comment, ground_state) in [
+            ("id1E1103CE Landed", Some(GroundState::Landed)),
+            ("Landed", Some(GroundState::Landed)),
+            ("id1E1103CE need a ride", Some(GroundState::NeedRide)),
+            (
+                "id1E1103CE Distress call automatically",
+                Some(GroundState::DistressCallAutomatically),
+            ),
+            ("id1E1103CE +000fpm", None),
+            ("id1E1103CE Unlanded", None),
+        ] {
+            assert_eq!(get_ground_state(comment), ground_state);
+        }
+    }
+
+    #[test]
+    fn names_expire() {
+        let sut = Names::default();
+        let id = String::from("11075F");
+
+        sut.insert(id.clone(), String::from("Mario"), 1000);
+
+        assert_eq!(sut.get(&id, 1000).as_deref(), Some("Mario"));
+        assert_eq!(
+            sut.get(&id, 1000 + MAX_STATUS_AGE).as_deref(),
+            Some("Mario")
+        );
+        assert_eq!(sut.get(&id, 1001 + MAX_STATUS_AGE), None);
+        assert_eq!(sut.get(&String::from("11123F"), 1000), None);
+
+        /* Expired names are removed eventually */
+        sut.insert(
+            String::from("11123F"),
+            String::from("Luigi"),
+            1001 + MAX_STATUS_AGE,
+        );
+        assert_eq!(sut.names.lock().unwrap().len(), 1);
+        assert_eq!(sut.get(&id, 1001 + MAX_STATUS_AGE), None);
+    }
+
+    #[test]
+    fn apply_name_skips_ddb_aircraft() {
+        let line = "FNT11075F>OGNFNT,qAS,Kraehbrg:/203010h4742.10N/00820.21E'000/000/A=001591 id1E11075F +000fpm";
+        let empty_ddb = HashMap::new();
+        let mut status = convert(line, &empty_ddb).unwrap();
+
+        let names = Names::default();
+        names.insert(
+            String::from("11075F"),
+            String::from("Mario"),
+            status.time_stamp,
+        );
+
+        apply_name(&mut status, &empty_ddb, &names);
+        assert_eq!(status.aircraft.name.as_deref(), Some("Mario"));
+
+        let ddb = HashMap::from([(
+            String::from("11075F"),
+            Aircraft {
+                id: String::from("11075F"),
+                registration: Some(String::from("D-MXXX")),
+                metadata_source: MetadataSource::Ddb,
+                ..Default::default()
+            },
+        )]);
+        let mut status = convert(line, &ddb).unwrap();
+        apply_name(&mut status, &ddb, &names);
+        assert_eq!(status.aircraft.name, None);
+    }
+}
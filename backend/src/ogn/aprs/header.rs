@@ -0,0 +1,127 @@
+use std::fmt::{Display, Formatter, Result};
+
+use serde::Serialize;
+
+/// Separates header and data of an APRS line
+const IDENTIFIER_DATA_SEPARATOR: char = ':';
+/// Separates source and destination / path of an APRS header
+const IDENTIFIER_SOURCE_SEPARATOR: char = '>';
+/// Separates the elements of an APRS path
+const IDENTIFIER_PATH_SEPARATOR: char = ',';
+/// Prefix of the q-construct, which is followed by the name of the receiver
+const IDENTIFIER_Q_CONSTRUCT: &str = "qA";
+
+/// Representation of the header of an APRS packet, e.g. `FLRDDE626>APRS,qAS,EGHL`
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct PacketHeader {
+    /// Callsign of the sender (e.g. "FLRDDE626")
+    pub source: String,
+    /// Destination, which identifies the sending software / device type (e.g. "APRS")
+    pub destination: String,
+    /// The q-construct that tells how the packet got into the APRS network (e.g. "qAS")
+    pub q_construct: Option<String>,
+    /// Name of the receiver (ground station) that heard the packet (e.g. "EGHL")
+    pub receiver: Option<String>,
+}
+
+/// Tries parsing the header of an APRS line
+///
+/// # Arguments
+///
+/// * `line` - The APRS line
+///
+/// # Returns
+///
+/// `PacketHeader` or `None` if the line has no valid header
+///
+/// # Examples
+///
+/// ```ignore
+/// let header = parse_header("FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'").unwrap();
+///
+/// assert_eq!(header.source, "FLRDDE626");
+/// assert_eq!(header.receiver, Some(String::from("EGHL")));
+/// ```
+pub fn parse_header(line: &str) -> Option<PacketHeader> {
+    let (header, _) = line.split_once(IDENTIFIER_DATA_SEPARATOR)?;
+    let (source, path) = header.split_once(IDENTIFIER_SOURCE_SEPARATOR)?;
+
+    if source.is_empty() {
+        return None;
+    }
+
+    let mut path_elements = path.split(IDENTIFIER_PATH_SEPARATOR);
+    let destination = path_elements.next().filter(|d| !d.is_empty())?;
+
+    /* The receiver is the element that follows the q-construct, e.g. "qAS,EGHL" */
+    let mut path_elements = path_elements.skip_while(|e| !e.starts_with(IDENTIFIER_Q_CONSTRUCT));
+    let q_construct = path_elements.next().map(String::from);
+    let receiver = path_elements.next().map(String::from);
+
+    Some(PacketHeader {
+        source: String::from(source),
+        destination: String::from(destination),
+        q_construct,
+        receiver,
+    })
+}
+
+impl Display for PacketHeader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "{}>{} (via {:?}, {:?})",
+            self.source, self.destination, self.q_construct, self.receiver
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_header_works() {
+        let header = parse_header(
+            "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 id0ADDE626",
+        )
+        .unwrap();
+
+        assert_eq!(header.source, "FLRDDE626");
+        assert_eq!(header.destination, "APRS");
+        assert_eq!(header.q_construct.as_deref(), Some("qAS"));
+        assert_eq!(header.receiver.as_deref(), Some("EGHL"));
+    }
+
+    #[test]
+    fn parse_header_skips_digipeaters() {
+        let header =
+            parse_header("ICA3D17F2>OGADSB,RELAY*,qAR,LFNW:/074548h5111.32N/00102.04W'").unwrap();
+
+        assert_eq!(header.source, "ICA3D17F2");
+        assert_eq!(header.destination, "OGADSB");
+        assert_eq!(header.q_construct.as_deref(), Some("qAR"));
+        assert_eq!(header.receiver.as_deref(), Some("LFNW"));
+    }
+
+    #[test]
+    fn parse_header_works_without_q_construct() {
+        let header = parse_header("FLRDDE626>APRS:/074548h5111.32N/00102.04W'").unwrap();
+
+        assert_eq!(header.destination, "APRS");
+        assert_eq!(header.q_construct, None);
+        assert_eq!(header.receiver, None);
+    }
+
+    #[test]
+    fn parse_header_rejects_invalid_headers() {
+        for line in [
+            "FLRDDE626 APRS,qAS,EGHL",
+            "FLRDDE626APRS,qAS,EGHL:/074548h",
+            ">APRS,qAS,EGHL:/074548h",
+            "FLRDDE626>:/074548h",
+        ] {
+            assert!(parse_header(line).is_none());
+        }
+    }
+}
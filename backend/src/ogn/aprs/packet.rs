@@ -0,0 +1,1379 @@
+use super::{
+    error::ParseError,
+    header::{parse_header, PacketHeader},
+};
+
+/// Separates header and data of an APRS line
+const IDENTIFIER_DATA_SEPARATOR: char = ':';
+/// Separates course and speed of the data extension "ccc/sss"
+const IDENTIFIER_COURSE_SPEED_SEPARATOR: u8 = b'/';
+/// Prefix of the altitude inside the comment, e.g. "/A=000607"
+const IDENTIFIER_ALTITUDE: &str = "/A=";
+/// Prefix of the aircraft id field "idXXYYYYYY"
+const IDENTIFIER_ID_FIELD: &str = "id";
+/// Prefix of the GPS accuracy field "gpsHxV"
+const IDENTIFIER_GPS_ACCURACY: &str = "gps";
+/// Separates horizontal and vertical value of the GPS accuracy field
+const IDENTIFIER_GPS_ACCURACY_SEPARATOR: char = 'x';
+
+/// Length of an APRS timestamp, e.g. "074548h"
+const LENGTH_TIMESTAMP: usize = 7;
+/// Length of an uncompressed latitude, e.g. "5111.32N"
+const LENGTH_LATITUDE: usize = 8;
+/// Length of an uncompressed longitude, e.g. "00102.04W"
+const LENGTH_LONGITUDE: usize = 9;
+/// Length of the data extension "ccc/sss"
+const LENGTH_COURSE_SPEED: usize = 7;
+/// Length of the altitude value of "/A=aaaaaa"
+const LENGTH_ALTITUDE: usize = 6;
+/// Length of a compressed position "/YYYYXXXX$csT"
+const LENGTH_COMPRESSED_POSITION: usize = 13;
+/// Number of digits of a Base91 encoded coordinate
+const LENGTH_BASE91_COORDINATE: usize = 4;
+
+/// Offset of Base91 digits in the ASCII table ('!' equals 0)
+const BASE91_OFFSET: u8 = 33;
+/// Number of Base91 digits
+const BASE91_RADIX: u32 = 91;
+/// Divisor to decode a compressed latitude
+const BASE91_LATITUDE_DIVISOR: f64 = 380_926.0;
+/// Divisor to decode a compressed longitude
+const BASE91_LONGITUDE_DIVISOR: f64 = 190_463.0;
+/// Compressed "c" byte that indicates that "cs" contains no data
+const COMPRESSED_NO_DATA: u8 = b' ';
+/// Compressed "c" byte that indicates that "cs" contains the radio range
+const COMPRESSED_RANGE: u8 = b'{';
+/// Bits of the compression type byte that contain the NMEA source
+const MASK_COMPRESSION_NMEA_SOURCE: u8 = 0b1_1000;
+/// NMEA source "GGA", which indicates that "cs" contains the altitude
+const COMPRESSION_NMEA_SOURCE_GGA: u8 = 0b1_0000;
+/// Base of the compressed speed (in knots), speed = 1.08^s - 1
+const COMPRESSED_SPEED_BASE: f64 = 1.08;
+/// Base of the compressed altitude (in ft), altitude = 1.002^cs
+const COMPRESSED_ALTITUDE_BASE: f64 = 1.002;
+/// Factor of the compressed course (in degrees), course = c * 4
+const COMPRESSED_COURSE_FACTOR: u16 = 4;
+
+/// Number of seconds per hour
+const SECONDS_PER_HOUR: u32 = 60 * 60;
+/// Number of seconds per minute
+const SECONDS_PER_MINUTE: u32 = 60;
+
+/// Data type identifier of an APRS packet, which is the first character of the data part
+///
+/// # References
+///
+/// - [APRS Protocol Reference, chapter 5](http://www.aprs.org/doc/APRS101.PDF)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DataType {
+    /// Position report without timestamp ('!' or '=')
+    PositionWithoutTimestamp,
+    /// Position report with timestamp ('/' or '@')
+    PositionWithTimestamp,
+    /// Status report ('>')
+    Status,
+    /// Any other packet type, which is not parsed any further
+    Other(char),
+}
+
+impl DataType {
+    /// Gets the data type for its identifier
+    ///
+    /// # Arguments
+    ///
+    /// * `identifier` - First character of the data part
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// assert_eq!(DataType::from_char('/'), DataType::PositionWithTimestamp);
+    /// assert_eq!(DataType::from_char(':'), DataType::Other(':'));
+    /// ```
+    fn from_char(identifier: char) -> DataType {
+        match identifier {
+            '!' | '=' => Self::PositionWithoutTimestamp,
+            '/' | '@' => Self::PositionWithTimestamp,
+            '>' => Self::Status,
+            _ => Self::Other(identifier),
+        }
+    }
+}
+
+/// Representation of an APRS packet, split into its tokens
+#[derive(Clone, Debug, PartialEq)]
+pub struct Packet {
+    /// The header, e.g. `FLRDDE626>APRS,qAS,EGHL`
+    pub header: PacketHeader,
+    /// The data type identifier
+    pub data_type: DataType,
+    /// The parsed data part
+    pub body: Body,
+}
+
+/// Parsed data part of an APRS packet, depending on its `DataType`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Body {
+    Position(PositionReport),
+    Status(StatusReport),
+    /// Raw data of unsupported packet types (without data type identifier)
+    Other(String),
+}
+
+/// Representation of an APRS position report
+///
+/// # Notes
+///
+/// Values are given in the units of the APRS specification (knots, ft, ...), conversion is up to
+/// the consumer.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PositionReport {
+    /// Seconds since midnight (UTC), if the report contains a timestamp
+    pub time_of_day: Option<u32>,
+    /// Latitude in degrees, including the precision enhancement "!Wxy!"
+    pub latitude: f64,
+    /// Longitude in degrees, including the precision enhancement "!Wxy!"
+    pub longitude: f64,
+    /// Symbol table identifier, e.g. '/'
+    pub symbol_table: char,
+    /// Symbol code, e.g. '\''
+    pub symbol_code: char,
+    /// Course in degrees
+    pub course: Option<u16>,
+    /// Speed in knots
+    pub speed: Option<u16>,
+    /// Altitude in ft
+    pub altitude: Option<i32>,
+    /// The comment and its extensions
+    pub comment: Comment,
+}
+
+/// Representation of an APRS status report (e.g. `>074555h v0.2.7.RPI-GPU CPU:0.7`)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StatusReport {
+    /// Seconds since midnight (UTC), if the report contains a timestamp
+    pub time_of_day: Option<u32>,
+    /// The status text
+    pub text: String,
+}
+
+/// Comment of a position report, split into the extensions of the OGN flavoured APRS
+///
+/// # References
+///
+/// - [OGN Wiki](http://wiki.glidernet.org/wiki:ogn-flavoured-aprs)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Comment {
+    /// The aircraft id field "idXXYYYYYY"
+    pub id: Option<IdField>,
+    /// Vertical speed in ft/min, e.g. "-019fpm"
+    pub vertical_speed: Option<f32>,
+    /// Turn rate in turns/2min, e.g. "+0.0rot"
+    pub turn_rate: Option<f32>,
+    /// Signal quality in dB, e.g. "5.5dB"
+    pub signal_quality: Option<f32>,
+    /// Number of corrected bit errors, e.g. "3e"
+    pub bit_errors: Option<u16>,
+    /// Frequency offset in kHz, e.g. "-4.3kHz"
+    pub frequency_offset: Option<f32>,
+    /// Horizontal and vertical GPS accuracy in m, e.g. "gps2x3"
+    pub gps_accuracy: Option<(u16, u16)>,
+    /// Additional digits of the latitude and longitude minutes, e.g. "!W12!"
+    pub precision: Option<(u8, u8)>,
+    /// The complete comment (without altitude)
+    pub text: String,
+}
+
+/// The aircraft id field "idXXYYYYYY", see `conversion::IdDetails`
+#[derive(Clone, Debug, PartialEq)]
+pub struct IdField {
+    /// The encoded id details "XX"
+    pub details: u8,
+    /// The id "YYYYYY"
+    pub id: String,
+}
+
+/// Tries parsing an APRS line into a `Packet`
+///
+/// # Arguments
+///
+/// * `line` - The APRS line
+///
+/// # Returns
+///
+/// The `Packet` or a `ParseError` if the line is no valid APRS packet
+///
+/// # Examples
+///
+/// ```ignore
+/// let line = "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 id0ADDE626";
+/// let packet = parse_packet(line).unwrap();
+///
+/// assert_eq!(packet.data_type, DataType::PositionWithTimestamp);
+/// assert!(matches!(packet.body, Body::Position(p) if p.altitude == Some(607)));
+/// ```
+pub fn parse_packet(line: &str) -> Result<Packet, ParseError> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let header = parse_header(line).ok_or(ParseError::Malformed)?;
+    let (_, data) = line
+        .split_once(IDENTIFIER_DATA_SEPARATOR)
+        .ok_or(ParseError::Malformed)?;
+
+    let mut chars = data.chars();
+    let data_type = DataType::from_char(chars.next().ok_or(ParseError::UnsupportedPacketType)?);
+    let data = chars.as_str();
+
+    let body = match data_type {
+        DataType::PositionWithTimestamp => {
+            let (timestamp, data) = data
+                .split_at_checked(LENGTH_TIMESTAMP)
+                .filter(|(t, _)| is_timestamp(t))
+                .ok_or(ParseError::NoPosition)?;
+
+            let mut report = parse_position(data)?;
+            report.time_of_day = parse_time_of_day(timestamp);
+            Body::Position(report)
+        }
+        DataType::PositionWithoutTimestamp => Body::Position(parse_position(data)?),
+        DataType::Status => Body::Status(parse_status(data)),
+        DataType::Other(_) => Body::Other(String::from(data)),
+    };
+
+    Ok(Packet {
+        header,
+        data_type,
+        body,
+    })
+}
+
+/// Parses the part of a position report after the (optional) timestamp, which is either
+/// uncompressed ("5111.32N/00102.04W'") or compressed ("/5L!!<*e7>7P[")
+///
+/// # Arguments
+///
+/// * `data` - The position, followed by data extension and comment
+fn parse_position(data: &str) -> Result<PositionReport, ParseError> {
+    match data.as_bytes().first() {
+        Some(c) if c.is_ascii_digit() => parse_uncompressed_position(data),
+        Some(_) => parse_compressed_position(data),
+        None => Err(ParseError::NoPosition),
+    }
+}
+
+/// Parses an uncompressed position "ddmm.mmNTdddmm.mmEC" followed by the optional data extension
+/// "ccc/sss" and the comment
+///
+/// # Arguments
+///
+/// * `data` - The position, followed by data extension and comment
+fn parse_uncompressed_position(data: &str) -> Result<PositionReport, ParseError> {
+    let (latitude, data) = data
+        .split_at_checked(LENGTH_LATITUDE)
+        .ok_or(ParseError::NoPosition)?;
+    let (symbol_table, data) = split_first_char(data).ok_or(ParseError::NoPosition)?;
+    let (longitude, data) = data
+        .split_at_checked(LENGTH_LONGITUDE)
+        .ok_or(ParseError::NoPosition)?;
+    let (symbol_code, data) = split_first_char(data).ok_or(ParseError::NoPosition)?;
+
+    if !is_coordinate(latitude, ['N', 'S']) || !is_coordinate(longitude, ['E', 'W']) {
+        return Err(ParseError::NoPosition);
+    }
+
+    let (course, speed, data) = match data.split_at_checked(LENGTH_COURSE_SPEED) {
+        Some((extension, rest)) if is_course_speed(extension) => (
+            extension[0..3].parse().ok(),
+            extension[4..7].parse().ok(),
+            rest,
+        ),
+        _ => (None, None, data),
+    };
+
+    let (altitude, comment) = parse_comment(data);
+    let (latitude_precision, longitude_precision) = comment.precision.unzip();
+
+    let latitude = parse_coordinate(latitude, latitude_precision)
+        .filter(|l| l.abs() <= 90.0)
+        .ok_or(ParseError::BadCoordinate)?;
+    let longitude = parse_coordinate(longitude, longitude_precision)
+        .filter(|l| l.abs() <= 180.0)
+        .ok_or(ParseError::BadCoordinate)?;
+
+    Ok(PositionReport {
+        time_of_day: None,
+        latitude,
+        longitude,
+        symbol_table,
+        symbol_code,
+        course,
+        speed,
+        altitude,
+        comment,
+    })
+}
+
+/// Parses a compressed position "TYYYYXXXXCcsT" followed by the comment. Depending on the
+/// compression type byte "T", the "cs" bytes contain either course and speed or the altitude.
+///
+/// # Arguments
+///
+/// * `data` - The compressed position, followed by the comment
+///
+/// # References
+///
+/// - [APRS Protocol Reference, chapter 9](http://www.aprs.org/doc/APRS101.PDF)
+fn parse_compressed_position(data: &str) -> Result<PositionReport, ParseError> {
+    let (position, data) = data
+        .split_at_checked(LENGTH_COMPRESSED_POSITION)
+        .filter(|(p, _)| is_compressed_position(p))
+        .ok_or(ParseError::NoPosition)?;
+
+    let bytes = position.as_bytes();
+    let latitude_digits = &bytes[1..=LENGTH_BASE91_COORDINATE];
+    let longitude_digits = &bytes[LENGTH_BASE91_COORDINATE + 1..=2 * LENGTH_BASE91_COORDINATE];
+
+    let latitude = 90.0 - f64::from(decode_base91(latitude_digits)) / BASE91_LATITUDE_DIVISOR;
+    let longitude = -180.0 + f64::from(decode_base91(longitude_digits)) / BASE91_LONGITUDE_DIVISOR;
+
+    if latitude.abs() > 90.0 || longitude.abs() > 180.0 {
+        return Err(ParseError::BadCoordinate);
+    }
+
+    let cs_offset = 2 * LENGTH_BASE91_COORDINATE + 2;
+    let (course, speed, compressed_altitude) =
+        decode_compressed_extension(bytes[cs_offset], bytes[cs_offset + 1], bytes[cs_offset + 2]);
+
+    /* An altitude in the comment ("/A=aaaaaa") is more precise than the compressed one */
+    let (altitude, comment) = parse_comment(data);
+
+    Ok(PositionReport {
+        time_of_day: None,
+        latitude,
+        longitude,
+        symbol_table: char::from(bytes[0]),
+        symbol_code: char::from(bytes[2 * LENGTH_BASE91_COORDINATE + 1]),
+        course,
+        speed,
+        altitude: altitude.or(compressed_altitude),
+        comment,
+    })
+}
+
+/// Parses a status report, consisting of an optional timestamp and the status text
+///
+/// # Arguments
+///
+/// * `data` - The data part without data type identifier
+fn parse_status(data: &str) -> StatusReport {
+    match data.split_at_checked(LENGTH_TIMESTAMP) {
+        Some((timestamp, text)) if is_timestamp(timestamp) => StatusReport {
+            time_of_day: parse_time_of_day(timestamp),
+            text: String::from(text.trim()),
+        },
+        _ => StatusReport {
+            time_of_day: None,
+            text: String::from(data.trim()),
+        },
+    }
+}
+
+/// Splits the comment of a position report into the altitude and its extensions
+///
+/// # Arguments
+///
+/// * `data` - The comment
+///
+/// # Returns
+///
+/// Altitude (in ft) and the `Comment`
+///
+/// # Examples
+///
+/// ```ignore
+/// let (altitude, comment) = parse_comment("/A=000607 id0ADDE626 -019fpm");
+///
+/// assert_eq!(altitude, Some(607));
+/// assert_eq!(comment.vertical_speed, Some(-19.0));
+/// ```
+fn parse_comment(data: &str) -> (Option<i32>, Comment) {
+    let mut altitude = None;
+    let mut text = String::from(data);
+
+    /* The altitude may appear anywhere in the comment, "/A=aaaaaa" with aaaaaa being feet */
+    if let Some(start) = data.find(IDENTIFIER_ALTITUDE) {
+        let value_start = start + IDENTIFIER_ALTITUDE.len();
+        let value_end = data[value_start..]
+            .char_indices()
+            .find(|&(i, c)| !(c.is_ascii_digit() || (i == 0 && c == '-')))
+            .map_or(data.len(), |(i, _)| value_start + i)
+            .min(value_start + LENGTH_ALTITUDE);
+
+        altitude = data[value_start..value_end].parse::<i32>().ok();
+
+        if altitude.is_some() {
+            text = format!("{}{}", &data[..start], &data[value_end..]);
+        }
+    }
+
+    let mut comment = Comment {
+        text: String::from(text.trim()),
+        ..Default::default()
+    };
+
+    for token in text.split_whitespace() {
+        comment.id = comment.id.or_else(|| parse_id_field(token));
+        comment.precision = comment.precision.or_else(|| parse_precision(token));
+        comment.gps_accuracy = comment.gps_accuracy.or_else(|| parse_gps_accuracy(token));
+        comment.vertical_speed = comment
+            .vertical_speed
+            .or_else(|| parse_suffixed(token, "fpm"));
+        comment.turn_rate = comment.turn_rate.or_else(|| parse_suffixed(token, "rot"));
+        comment.signal_quality = comment
+            .signal_quality
+            .or_else(|| parse_suffixed(token, "dB"));
+        comment.bit_errors = comment.bit_errors.or_else(|| parse_suffixed(token, "e"));
+        comment.frequency_offset = comment
+            .frequency_offset
+            .or_else(|| parse_suffixed(token, "kHz"));
+    }
+
+    (altitude, comment)
+}
+
+/// Tries parsing a comment token that consists of a number and a unit (e.g. "-019fpm")
+///
+/// # Arguments
+///
+/// * `token` - The comment token
+/// * `suffix` - The unit that the token has to end with
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(parse_suffixed::<f32>("-4.3kHz", "kHz"), Some(-4.3));
+/// assert_eq!(parse_suffixed::<f32>("-4.3kHz", "dB"), None);
+/// ```
+fn parse_suffixed<T: std::str::FromStr>(token: &str, suffix: &str) -> Option<T> {
+    let value = token.strip_suffix(suffix)?;
+
+    /* `FromStr` of floats would also accept values like "inf" or "NaN" */
+    if !value.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+') {
+        return None;
+    }
+
+    value.parse().ok()
+}
+
+/// Tries parsing the aircraft id field "idXXYYYYYY"
+///
+/// # Arguments
+///
+/// * `token` - The comment token
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(
+///     parse_id_field("id0ADDE626"),
+///     Some(IdField { details: 0x0A, id: String::from("DDE626") })
+/// );
+/// ```
+fn parse_id_field(token: &str) -> Option<IdField> {
+    let value = token.strip_prefix(IDENTIFIER_ID_FIELD)?;
+    let (details, id) = value.split_at_checked(2)?;
+
+    if id.is_empty()
+        || !details.chars().all(|c| c.is_ascii_hexdigit())
+        || !id.chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        return None;
+    }
+
+    Some(IdField {
+        details: u8::from_str_radix(details, 16).ok()?,
+        id: String::from(id),
+    })
+}
+
+/// Tries parsing the precision enhancement "!Wxy!"
+///
+/// # Arguments
+///
+/// * `token` - The comment token
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(parse_precision("!W12!"), Some((1, 2)));
+/// ```
+fn parse_precision(token: &str) -> Option<(u8, u8)> {
+    let digits = token.strip_prefix("!W")?.strip_suffix('!')?.as_bytes();
+
+    match digits {
+        [latitude, longitude] if latitude.is_ascii_digit() && longitude.is_ascii_digit() => {
+            Some((latitude - b'0', longitude - b'0'))
+        }
+        _ => None,
+    }
+}
+
+/// Tries parsing the GPS accuracy field "gpsHxV"
+///
+/// # Arguments
+///
+/// * `token` - The comment token
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(parse_gps_accuracy("gps2x3"), Some((2, 3)));
+/// ```
+fn parse_gps_accuracy(token: &str) -> Option<(u16, u16)> {
+    let (horizontal, vertical) = token
+        .strip_prefix(IDENTIFIER_GPS_ACCURACY)?
+        .split_once(IDENTIFIER_GPS_ACCURACY_SEPARATOR)?;
+
+    if !horizontal
+        .chars()
+        .chain(vertical.chars())
+        .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    Some((horizontal.parse().ok()?, vertical.parse().ok()?))
+}
+
+/// Converts an uncompressed APRS coordinate value to a latitude / longitude value
+///
+/// # Arguments
+///
+/// * `value` - The coordinate, e.g. "1029.35S"
+/// * `precision` - Optional additional digit of the minutes (thousandths), as given by the APRS
+///   precision enhancement "!Wxy!"
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(parse_coordinate("1029.35S", None), Some(-10.489166666666666));
+/// assert_eq!(parse_coordinate("1029.35S", Some(5)), Some(-10.48925));
+/// ```
+fn parse_coordinate(value: &str, precision: Option<u8>) -> Option<f64> {
+    /* Latitude and longitude (by APRS-standard) are given as following: ddmm.mmD where d = "degree",
+     * m = "minute" and D = "direction".
+     * Notice that minutes are decimals, so 0.5 minutes equal 0 minutes, 30 secs.
+     * We'll separate degrees and minutes, so we can convert it to a "degree"-only value. */
+
+    let orientation = value.chars().last()?; /* "N", "E", "S" or "W" */
+    let aprs_value = value[..value.len() - orientation.len_utf8()]
+        .parse::<f64>()
+        .ok()?;
+
+    let degrees = f64::floor(aprs_value / 100.0); // Separating "dd" from "ddmm.mm"
+    let mut minutes = aprs_value - degrees * 100.0; // Separating "mm.mm" from "ddmm.mm"
+
+    if let Some(precision) = precision {
+        /* "!Wxy!" adds another digit to the minutes, so "mm.mm" becomes "mm.mmx" */
+        minutes += f64::from(precision) / 1000.0;
+    }
+
+    if !(0.0..60.0).contains(&minutes) {
+        return None;
+    }
+
+    let value = degrees + minutes / 60.0; // because 60 minutes = 1 degree
+
+    if orientation == 'S' || orientation == 'W' {
+        Some(-value)
+    } else {
+        Some(value)
+    }
+}
+
+/// Converts an APRS timestamp to the seconds of the day (UTC)
+///
+/// # Arguments
+///
+/// * `timestamp` - The timestamp, either "HHMMSSh" or "DDHHMMz". Local time ("DDHHMM/") is
+///   not supported.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(parse_time_of_day("074548h"), Some(27948));
+/// assert_eq!(parse_time_of_day("170745z"), Some(27900));
+/// ```
+fn parse_time_of_day(timestamp: &str) -> Option<u32> {
+    let value = |range: std::ops::Range<usize>| timestamp.get(range)?.parse::<u32>().ok();
+
+    let (hours, minutes, seconds) = match timestamp.as_bytes().get(6)? {
+        b'h' => (value(0..2)?, value(2..4)?, value(4..6)?),
+        b'z' => (value(2..4)?, value(4..6)?, 0),
+        _ => return None,
+    };
+
+    if hours >= 24 || minutes >= 60 || seconds >= 60 {
+        return None;
+    }
+
+    Some(hours * SECONDS_PER_HOUR + minutes * SECONDS_PER_MINUTE + seconds)
+}
+
+/// Decodes a Base91 value, which consists of printable ASCII characters
+///
+/// # Arguments
+///
+/// * `digits` - The Base91 digits, most significant first
+fn decode_base91(digits: &[u8]) -> u32 {
+    digits.iter().fold(0, |value, digit| {
+        value * BASE91_RADIX + u32::from(digit - BASE91_OFFSET)
+    })
+}
+
+/// Decodes the "csT" bytes of a compressed position
+///
+/// # Arguments
+///
+/// * `c` - First byte of "cs"
+/// * `s` - Second byte of "cs"
+/// * `compression_type` - The compression type byte "T"
+///
+/// # Returns
+///
+/// Course (in degrees), speed (in knots) and altitude (in ft), as far as given
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(decode_compressed_extension(b'7', b'P', b'['), (Some(88), Some(36), None));
+/// assert_eq!(decode_compressed_extension(b'S', b']', b'1'), (None, None, Some(10004)));
+/// ```
+///
+/// # References
+///
+/// - [APRS Protocol Reference, chapter 9](http://www.aprs.org/doc/APRS101.PDF)
+fn decode_compressed_extension(
+    c: u8,
+    s: u8,
+    compression_type: u8,
+) -> (Option<u16>, Option<u16>, Option<i32>) {
+    if c == COMPRESSED_NO_DATA {
+        return (None, None, None);
+    }
+
+    let c_value = c - BASE91_OFFSET;
+    let s_value = s - BASE91_OFFSET;
+
+    if (compression_type - BASE91_OFFSET) & MASK_COMPRESSION_NMEA_SOURCE
+        == COMPRESSION_NMEA_SOURCE_GGA
+    {
+        let exponent = decode_base91(&[c, s]);
+        let altitude = COMPRESSED_ALTITUDE_BASE.powf(f64::from(exponent));
+
+        /* Truncated like in the APRS reference. The exponent is < 91 * 91, so the altitude is
+         * < 1.002^8281 ft, which fits into i32. */
+        #[allow(clippy::cast_possible_truncation)]
+        return (None, None, Some(altitude as i32));
+    }
+
+    if c == COMPRESSED_RANGE {
+        return (None, None, None);
+    }
+
+    let course = u16::from(c_value) * COMPRESSED_COURSE_FACTOR;
+    let speed = COMPRESSED_SPEED_BASE.powi(i32::from(s_value)) - 1.0;
+
+    /* Truncated like in the APRS reference. s is < 91, so the speed is < 1.08^91 kt, which
+     * fits into u16. */
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    (Some(course), Some(speed as u16), None)
+}
+
+/// Checks whether a value is an APRS timestamp ("dddddd" followed by 'h', 'z' or '/')
+fn is_timestamp(value: &str) -> bool {
+    let bytes = value.as_bytes();
+
+    bytes.len() == LENGTH_TIMESTAMP
+        && bytes[..6].iter().all(u8::is_ascii_digit)
+        && matches!(bytes[6], b'h' | b'z' | b'/')
+}
+
+/// Checks whether a value is an uncompressed coordinate ("ddmm.mmD" or "dddmm.mmD")
+fn is_coordinate(value: &str, orientations: [char; 2]) -> bool {
+    let Some((number, orientation)) = value.split_at_checked(value.len().saturating_sub(1)) else {
+        return false;
+    };
+    let Some((integer, fraction)) = number.split_once('.') else {
+        return false;
+    };
+
+    orientation.chars().all(|c| orientations.contains(&c))
+        && fraction.len() == 2
+        && integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+}
+
+/// Checks whether a value is the data extension "ccc/sss"
+fn is_course_speed(value: &str) -> bool {
+    value.bytes().enumerate().all(|(i, c)| {
+        if i == 3 {
+            c == IDENTIFIER_COURSE_SPEED_SEPARATOR
+        } else {
+            c.is_ascii_digit()
+        }
+    })
+}
+
+/// Checks whether a value is a compressed position "TYYYYXXXXCcsT"
+fn is_compressed_position(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    let is_base91 = |c: &u8| (BASE91_OFFSET..BASE91_OFFSET + 91).contains(c);
+
+    bytes.len() == LENGTH_COMPRESSED_POSITION
+        && matches!(bytes[0], b'/' | b'\\' | b'A'..=b'Z' | b'a'..=b'j')
+        && bytes[1..=2 * LENGTH_BASE91_COORDINATE]
+            .iter()
+            .all(is_base91)
+        && bytes.is_ascii()
+        && (bytes[2 * LENGTH_BASE91_COORDINATE + 2] == COMPRESSED_NO_DATA
+            || bytes[2 * LENGTH_BASE91_COORDINATE + 2..]
+                .iter()
+                .all(is_base91))
+}
+
+/// Splits the first char from a value
+fn split_first_char(value: &str) -> Option<(char, &str)> {
+    let mut chars = value.chars();
+    let first = chars.next()?;
+
+    Some((first, chars.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{collection::vec, prelude::*};
+
+    use super::*;
+
+    /// Expected values of a position report fixture
+    struct Fixture {
+        line: &'static str,
+        time_of_day: Option<u32>,
+        latitude: f64,
+        longitude: f64,
+        course: Option<u16>,
+        speed: Option<u16>,
+        altitude: Option<i32>,
+        id: Option<(u8, &'static str)>,
+        vertical_speed: Option<f32>,
+        turn_rate: Option<f32>,
+    }
+
+    /// Position reports in the formats of the OGN flavoured APRS specification, including at least
+    /// one per supported destination call (and one without timestamp)
+    ///
+    /// # References
+    ///
+    /// - [OGN Wiki](http://wiki.glidernet.org/wiki:ogn-flavoured-aprs)
+    const FIXTURES: [Fixture; 14] = [
+        Fixture {
+            line: "FLRDDA5BA>APRS,qAS,LFMX:/160829h4415.41N/00600.03E'342/049/A=005524 id0ADDA5BA -454fpm -1.1rot 8.8dB 0e +51.2kHz gps4x5",
+            time_of_day: Some(58109),
+            latitude: 44.25683333333333,
+            longitude: 6.0005,
+            course: Some(342),
+            speed: Some(49),
+            altitude: Some(5524),
+            id: Some((0x0A, "DDA5BA")),
+            vertical_speed: Some(-454.0),
+            turn_rate: Some(-1.1),
+        },
+        Fixture {
+            line: "ICA4B0E3A>APRS,qAS,Letzi:/072319h4711.75N\\00802.59E^327/149/A=006498 id154B0E3A -395fpm +0.0rot",
+            time_of_day: Some(26599),
+            latitude: 47.19583333333333,
+            longitude: 8.043166666666666,
+            course: Some(327),
+            speed: Some(149),
+            altitude: Some(6498),
+            id: Some((0x15, "4B0E3A")),
+            vertical_speed: Some(-395.0),
+            turn_rate: Some(0.0),
+        },
+        Fixture {
+            line: "OGN82149C>OGNTRK,qAS,Hochkoen:/121125h4727.08N/01304.85E'000/000/A=003602 !W55! id0782149C +000fpm +0.0rot FL036.45 45.5dB 0e +0.4kHz gps3x5",
+            time_of_day: Some(43885),
+            latitude: 47.45141666666667,
+            longitude: 13.080916666666667,
+            course: Some(0),
+            speed: Some(0),
+            altitude: Some(3602),
+            id: Some((0x07, "82149C")),
+            vertical_speed: Some(0.0),
+            turn_rate: Some(0.0),
+        },
+        Fixture {
+            line: "ICA3D17F2>OGADSB,qAS,dl4mea:/074549h4821.61N\\01224.49E^322/444/A=012369 !W81! id253D17F2 -0384fpm FL123.35 A3:AFR123",
+            time_of_day: Some(27949),
+            latitude: 48.3603,
+            longitude: 12.408183333333334,
+            course: Some(322),
+            speed: Some(444),
+            altitude: Some(12369),
+            id: Some((0x25, "3D17F2")),
+            vertical_speed: Some(-384.0),
+            turn_rate: None,
+        },
+        Fixture {
+            line: "FNT11075F>OGNFNT,qAS,Kraehbrg:/203010h4742.10N/00820.21E'000/000/A=001591 !W77! id1E11075F +000fpm",
+            time_of_day: Some(73810),
+            latitude: 47.70178333333333,
+            longitude: 8.33695,
+            course: Some(0),
+            speed: Some(0),
+            altitude: Some(1591),
+            id: Some((0x1E, "11075F")),
+            vertical_speed: Some(0.0),
+            turn_rate: None,
+        },
+        Fixture {
+            line: "ICA3E7540>OGSPOT,qAS,SPOT:/161427h1448.35S/04610.86W'000/000/A=008677 id0-2860357 SPOT3 GOOD",
+            time_of_day: Some(58467),
+            latitude: -14.805833333333334,
+            longitude: -46.181,
+            course: Some(0),
+            speed: Some(0),
+            altitude: Some(8677),
+            id: None,
+            vertical_speed: None,
+            turn_rate: None,
+        },
+        Fixture {
+            line: "LKHS>OGNSDR,TCPIP*,qAC,GLIDERN2:/211635h4902.45NI01429.51E&000/000/A=001689",
+            time_of_day: Some(76595),
+            latitude: 49.0408333333333,
+            longitude: 14.491833333333334,
+            course: Some(0),
+            speed: Some(0),
+            altitude: Some(1689),
+            id: None,
+            vertical_speed: None,
+            turn_rate: None,
+        },
+        Fixture {
+            line: "FLRDDE626>APRS,qAS,EGHL:!5111.32N/00102.04W'/A=-01312 id0ADDE626",
+            time_of_day: None,
+            latitude: 51.18866666666666,
+            longitude: -1.034,
+            course: None,
+            speed: None,
+            altitude: Some(-1312),
+            id: Some((0x0A, "DDE626")),
+            vertical_speed: None,
+            turn_rate: None,
+        },
+        Fixture {
+            line: "FLRDDEEF1>OGFLR,qAS,LSTB:/154459h4645.38N/00752.71E'224/052/A=002346 !W29! id06DDEEF1 +238fpm -0.5rot 7.3dB 0e -3.4kHz gps2x3",
+            time_of_day: Some(56699),
+            latitude: 46.756366666666665,
+            longitude: 7.87865,
+            course: Some(224),
+            speed: Some(52),
+            altitude: Some(2346),
+            id: Some((0x06, "DDEEF1")),
+            vertical_speed: Some(238.0),
+            turn_rate: Some(-0.5),
+        },
+        /* NAVITER sends 4 digits of id details, which are split like any other id field here */
+        Fixture {
+            line: "NAV04220E>OGNAVI,qAS,NAVITER:/140648h4550.36N/01314.85E'090/152/A=001086 !W47! id0440042121 +000fpm +0.5rot",
+            time_of_day: Some(50808),
+            latitude: 45.8394,
+            longitude: 13.247616666666667,
+            course: Some(90),
+            speed: Some(152),
+            altitude: Some(1086),
+            id: Some((0x04, "40042121")),
+            vertical_speed: Some(0.0),
+            turn_rate: Some(0.5),
+        },
+        /* Sources that are identified by their callsign send other ids, which are split like id
+         * fields here, but not used by the conversion */
+        Fixture {
+            line: "FLRDDDD33>OGSKYL,qAS,SKYLINES:/134403h4225.90N/00144.83E'000/000/A=008438 id2816 +000fpm",
+            time_of_day: Some(49443),
+            latitude: 42.431666666666665,
+            longitude: 1.7471666666666668,
+            course: Some(0),
+            speed: Some(0),
+            altitude: Some(8438),
+            id: Some((0x28, "16")),
+            vertical_speed: Some(0.0),
+            turn_rate: None,
+        },
+        Fixture {
+            line: "LT24F8B44>OGLT24,qAS,LT24:/102606h4030.47N/00338.38W'000/018/A=002267 id25387 +000fpm GPS",
+            time_of_day: Some(37566),
+            latitude: 40.50783333333333,
+            longitude: -3.639666666666667,
+            course: Some(0),
+            speed: Some(18),
+            altitude: Some(2267),
+            id: Some((0x25, "387")),
+            vertical_speed: Some(0.0),
+            turn_rate: None,
+        },
+        Fixture {
+            line: "FLRDDEEF1>OGCAPT,qAS,CAPTURS:/065511h4837.63N/00233.79E'000/000/A=000000",
+            time_of_day: Some(24911),
+            latitude: 48.62716666666667,
+            longitude: 2.5631666666666666,
+            course: Some(0),
+            speed: Some(0),
+            altitude: Some(0),
+            id: None,
+            vertical_speed: None,
+            turn_rate: None,
+        },
+        Fixture {
+            line: "PAW404CB8>OGPAW,qAS,UKBSO:/094045h5146.42N/00111.33W'000/000/A=000000 !W26! id1C404CB8 10.8dB +1.4kHz",
+            time_of_day: Some(34845),
+            latitude: 51.7737,
+            longitude: -1.1889333333333334,
+            course: Some(0),
+            speed: Some(0),
+            altitude: Some(0),
+            id: Some((0x1C, "404CB8")),
+            vertical_speed: None,
+            turn_rate: None,
+        },
+    ];
+
+    /// Encodes a coordinate as "ddmm.mmD" and the thousandths of the minutes, as used by "!Wxy!"
+    fn encode_coordinate(value: f64, width: usize, orientations: (char, char)) -> (String, u8) {
+        let orientation = if value < 0.0 {
+            orientations.1
+        } else {
+            orientations.0
+        };
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let thousandths = (value.abs() * 60_000.0).round() as u64;
+        let degrees = thousandths / 60_000;
+        let minutes = thousandths % 60_000;
+
+        (
+            format!(
+                "{degrees:0width$}{:02}.{:02}{orientation}",
+                minutes / 1000,
+                minutes % 1000 / 10
+            ),
+            u8::try_from(minutes % 10).unwrap(),
+        )
+    }
+
+    /// Encodes a position report as uncompressed APRS line
+    fn encode_line(header: &str, report: &PositionReport) -> String {
+        let (latitude, latitude_precision) = encode_coordinate(report.latitude, 2, ('N', 'S'));
+        let (longitude, longitude_precision) = encode_coordinate(report.longitude, 3, ('E', 'W'));
+
+        let mut line = match report.time_of_day {
+            Some(time_of_day) => format!(
+                "{header}:/{:02}{:02}{:02}h",
+                time_of_day / 3600,
+                time_of_day % 3600 / 60,
+                time_of_day % 60
+            ),
+            None => format!("{header}:!"),
+        };
+
+        line.push_str(&format!(
+            "{latitude}{}{longitude}{}",
+            report.symbol_table, report.symbol_code
+        ));
+
+        if let (Some(course), Some(speed)) = (report.course, report.speed) {
+            line.push_str(&format!("{course:03}/{speed:03}"));
+        }
+
+        if let Some(altitude) = report.altitude {
+            line.push_str(&format!("/A={altitude:06}"));
+        }
+
+        line.push_str(&format!(" !W{latitude_precision}{longitude_precision}!"));
+
+        if let Some(id) = &report.comment.id {
+            line.push_str(&format!(" id{:02X}{}", id.details, id.id));
+        }
+
+        line
+    }
+
+    fn parse_position_report(line: &str) -> PositionReport {
+        let Ok(Packet {
+            body: Body::Position(report),
+            ..
+        }) = parse_packet(line)
+        else {
+            panic!("Expected position report for '{line}'");
+        };
+
+        report
+    }
+
+    #[test]
+    fn parse_packet_handles_specification_examples() {
+        for fixture in &FIXTURES {
+            let report = parse_position_report(fixture.line);
+
+            assert_eq!(report.time_of_day, fixture.time_of_day, "{}", fixture.line);
+            assert!(
+                (report.latitude - fixture.latitude).abs() < 1e-9,
+                "{}",
+                fixture.line
+            );
+            assert!(
+                (report.longitude - fixture.longitude).abs() < 1e-9,
+                "{}",
+                fixture.line
+            );
+            assert_eq!(report.course, fixture.course, "{}", fixture.line);
+            assert_eq!(report.speed, fixture.speed, "{}", fixture.line);
+            assert_eq!(report.altitude, fixture.altitude, "{}", fixture.line);
+            assert_eq!(
+                report
+                    .comment
+                    .id
+                    .as_ref()
+                    .map(|i| (i.details, i.id.as_str())),
+                fixture.id,
+                "{}",
+                fixture.line
+            );
+            assert_eq!(
+                report.comment.vertical_speed, fixture.vertical_speed,
+                "{}",
+                fixture.line
+            );
+            assert_eq!(
+                report.comment.turn_rate, fixture.turn_rate,
+                "{}",
+                fixture.line
+            );
+        }
+    }
+
+    #[test]
+    fn parse_packet_round_trips_specification_examples() {
+        for fixture in &FIXTURES {
+            let report = parse_position_report(fixture.line);
+            let (header, _) = fixture.line.split_once(':').unwrap();
+
+            let line = encode_line(header, &report);
+            let round_trip = parse_position_report(&line);
+
+            assert_eq!(round_trip.time_of_day, report.time_of_day, "{line}");
+            assert!(
+                (round_trip.latitude - report.latitude).abs() < 1e-9,
+                "{line}"
+            );
+            assert!(
+                (round_trip.longitude - report.longitude).abs() < 1e-9,
+                "{line}"
+            );
+            assert_eq!(round_trip.symbol_table, report.symbol_table, "{line}");
+            assert_eq!(round_trip.symbol_code, report.symbol_code, "{line}");
+            assert_eq!(round_trip.course, report.course, "{line}");
+            assert_eq!(round_trip.speed, report.speed, "{line}");
+            assert_eq!(round_trip.altitude, report.altitude, "{line}");
+            assert_eq!(round_trip.comment.id, report.comment.id, "{line}");
+        }
+    }
+
+    #[test]
+    fn parse_packet_parses_comment_extensions() {
+        let report = parse_position_report(FIXTURES[0].line);
+
+        assert_eq!(report.symbol_table, '/');
+        assert_eq!(report.symbol_code, '\'');
+        assert_eq!(report.comment.signal_quality, Some(8.8));
+        assert_eq!(report.comment.bit_errors, Some(0));
+        assert_eq!(report.comment.frequency_offset, Some(51.2));
+        assert_eq!(report.comment.gps_accuracy, Some((4, 5)));
+        assert_eq!(report.comment.precision, None);
+        assert_eq!(
+            report.comment.text,
+            "id0ADDA5BA -454fpm -1.1rot 8.8dB 0e +51.2kHz gps4x5"
+        );
+
+        let report = parse_position_report(FIXTURES[2].line);
+        assert_eq!(report.comment.precision, Some((5, 5)));
+    }
+
+    #[test]
+    fn parse_packet_parses_status_reports() {
+        let packet = parse_packet("LKHS>OGNSDR,TCPIP*,qAC,GLIDERN2:>211635h v0.2.6.ARM CPU:0.2 RAM:777.7/972.2MB NTP:3.1ms/-3.8ppm +33.6C\r\n").unwrap();
+
+        assert_eq!(packet.header.source, "LKHS");
+        assert_eq!(packet.data_type, DataType::Status);
+        assert_eq!(
+            packet.body,
+            Body::Status(StatusReport {
+                time_of_day: Some(76595),
+                text: String::from("v0.2.6.ARM CPU:0.2 RAM:777.7/972.2MB NTP:3.1ms/-3.8ppm +33.6C"),
+            })
+        );
+
+        let packet =
+            parse_packet("FNT11123F>OGNFNT,qAS,Huenenbg:>202135h Name=\"Mario\" 14.9dB").unwrap();
+        assert!(matches!(packet.body, Body::Status(s) if s.text == "Name=\"Mario\" 14.9dB"));
+    }
+
+    #[test]
+    fn parse_packet_parses_compressed_positions() {
+        /* Example of the APRS Protocol Reference, chapter 9 */
+        let report = parse_position_report("TEST>APRS,qAS,EGHL:/092345z/5L!!<*e7> sT comment");
+
+        assert_eq!(report.time_of_day, Some(85_500));
+        assert!((report.latitude - 49.5).abs() < 1e-4);
+        assert!((report.longitude - -72.75).abs() < 1e-4);
+        assert_eq!(report.symbol_table, '/');
+        assert_eq!(report.symbol_code, '>');
+        assert_eq!(report.course, None);
+        assert_eq!(report.speed, None);
+        assert_eq!(report.altitude, None);
+        assert_eq!(report.comment.text, "comment");
+    }
+
+    #[test]
+    fn parse_packet_parses_compressed_extensions() {
+        let report = parse_position_report("TEST>APRS,qAS,EGHL:!/5L!!<*e7>7P[");
+        assert_eq!(report.course, Some(88));
+        assert_eq!(report.speed, Some(36));
+        assert_eq!(report.altitude, None);
+
+        let report = parse_position_report("TEST>APRS,qAS,EGHL:!/5L!!<*e7>S]1");
+        assert_eq!(report.course, None);
+        assert_eq!(report.speed, None);
+        assert_eq!(report.altitude, Some(10004));
+
+        /* Radio range isn't supported */
+        let report = parse_position_report("TEST>APRS,qAS,EGHL:!/5L!!<*e7>{?!");
+        assert_eq!(report.course, None);
+        assert_eq!(report.speed, None);
+
+        /* Altitude of the comment wins over the compressed one */
+        let report = parse_position_report("TEST>APRS,qAS,EGHL:!/5L!!<*e7>S]1/A=010010 id0ADDE626");
+        assert_eq!(report.altitude, Some(10010));
+        assert!(report.comment.id.is_some());
+
+        assert_eq!(
+            parse_packet("TEST>APRS,qAS,EGHL:!/5L!!<*e7>7\u{e4}"),
+            Err(ParseError::NoPosition)
+        );
+    }
+
+    #[test]
+    fn decode_compressed_extension_works() {
+        assert_eq!(
+            decode_compressed_extension(b'7', b'P', b'['),
+            (Some(88), Some(36), None)
+        );
+        assert_eq!(
+            decode_compressed_extension(b'!', b'!', b'['),
+            (Some(0), Some(0), None)
+        );
+        assert_eq!(
+            decode_compressed_extension(b'z', b'{', b'['),
+            (Some(356), Some(1017), None)
+        );
+        assert_eq!(
+            decode_compressed_extension(b'S', b']', b'1'),
+            (None, None, Some(10004))
+        );
+        assert_eq!(
+            decode_compressed_extension(b'{', b'{', b'1'),
+            (None, None, Some(15_301_509))
+        );
+        assert_eq!(
+            decode_compressed_extension(b' ', b'x', b'x'),
+            (None, None, None)
+        );
+    }
+
+    #[test]
+    fn parse_packet_keeps_other_packet_types() {
+        let packet = parse_packet("FLRDDE626>APRS,qAS,EGHL::EGHL     :Hello").unwrap();
+
+        assert_eq!(packet.data_type, DataType::Other(':'));
+        assert_eq!(packet.body, Body::Other(String::from("EGHL     :Hello")));
+    }
+
+    #[test]
+    fn parse_packet_returns_parse_errors() {
+        for (line, parse_error) in [
+            ("FLRDDE626>APRS,qAS,EGHL", ParseError::Malformed),
+            (
+                "FLRDDE626>APRS,qAS,EGHL:",
+                ParseError::UnsupportedPacketType,
+            ),
+            (
+                "FLRDDE626>APRS,qAS,EGHL:/074548h id0AAB1234",
+                ParseError::NoPosition,
+            ),
+            (
+                "FLRDDE626>APRS,qAS,EGHL:/0745h5111.32N/00102.04W'",
+                ParseError::NoPosition,
+            ),
+            (
+                "FLRDDE626>APRS,qAS,EGHL:/074548h51.1.32N/00102.04W'",
+                ParseError::NoPosition,
+            ),
+            (
+                "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04",
+                ParseError::NoPosition,
+            ),
+            (
+                "FLRDDE626>APRS,qAS,EGHL:/074548h5160.00N/00102.04W'",
+                ParseError::BadCoordinate,
+            ),
+            (
+                "FLRDDE626>APRS,qAS,EGHL:/074548h9111.32N/00102.04W'",
+                ParseError::BadCoordinate,
+            ),
+            (
+                "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/18102.04W'",
+                ParseError::BadCoordinate,
+            ),
+            (
+                "FLRDDE626>APRS,qAS,EGHL:!51\u{e4}1.32N/00102.04W'",
+                ParseError::NoPosition,
+            ),
+        ] {
+            assert_eq!(parse_packet(line), Err(parse_error), "{line}");
+        }
+    }
+
+    #[test]
+    fn parse_coordinate_works() {
+        assert_eq!(parse_coordinate("5111.32N", None), Some(51.18866666666666));
+        assert_eq!(
+            parse_coordinate("1029.35S", None),
+            Some(-10.489166666666666)
+        );
+        assert_eq!(
+            parse_coordinate("5111.32N", Some(1)),
+            Some(51.18868333333333)
+        );
+        assert_eq!(
+            parse_coordinate("00102.04W", Some(2)),
+            Some(-1.0340333333333334)
+        );
+        assert_eq!(parse_coordinate("5160.00N", None), None);
+    }
+
+    #[test]
+    fn parse_time_of_day_works() {
+        assert_eq!(parse_time_of_day("074548h"), Some(27948));
+        assert_eq!(parse_time_of_day("000000h"), Some(0));
+        assert_eq!(parse_time_of_day("235959h"), Some(86399));
+        assert_eq!(parse_time_of_day("092345z"), Some(85_500));
+        assert_eq!(parse_time_of_day("092345/"), None);
+        assert_eq!(parse_time_of_day("240000h"), None);
+        assert_eq!(parse_time_of_day("006000h"), None);
+        assert_eq!(parse_time_of_day("12345h"), None);
+    }
+
+    #[test]
+    fn parse_comment_works() {
+        let (altitude, comment) =
+            parse_comment("/A=000607 id0AAB1234 -019fpm +0.0rot 5.5dB 3e -4.3kHz gps2x3");
+
+        assert_eq!(altitude, Some(607));
+        assert_eq!(
+            comment.id,
+            Some(IdField {
+                details: 0x0A,
+                id: String::from("AB1234"),
+            })
+        );
+        assert_eq!(comment.vertical_speed, Some(-19.0));
+        assert_eq!(comment.turn_rate, Some(0.0));
+        assert_eq!(comment.signal_quality, Some(5.5));
+        assert_eq!(comment.bit_errors, Some(3));
+        assert_eq!(comment.frequency_offset, Some(-4.3));
+        assert_eq!(comment.gps_accuracy, Some((2, 3)));
+
+        let (altitude, comment) = parse_comment(" idZZAB1234 id0A inffpm NaNdB one !W1!");
+
+        assert_eq!(altitude, None);
+        assert_eq!(
+            comment,
+            Comment {
+                text: String::from("idZZAB1234 id0A inffpm NaNdB one !W1!"),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn parse_comment_handles_altitude_beyond_u16() {
+        let (altitude, comment) = parse_comment("/A=250000 FL2500.00");
+
+        assert_eq!(altitude, Some(250_000));
+        assert_eq!(comment.text, "FL2500.00");
+    }
+
+    proptest! {
+        #[test]
+        fn parse_packet_never_panics_on_arbitrary_bytes(bytes in vec(any::<u8>(), 0..256)) {
+            let _ = parse_packet(&String::from_utf8_lossy(&bytes));
+        }
+
+        #[test]
+        fn parse_packet_never_panics_on_arbitrary_data(data in "[!/=@>:][ -~]{0,80}") {
+            let _ = parse_packet(&format!("FLRDDE626>APRS,qAS,EGHL:{data}"));
+        }
+
+        #[test]
+        fn parse_packet_round_trips_generated_reports(
+            time_of_day in 0u32..86_400,
+            latitude in -89.99f64..89.99,
+            longitude in -179.99f64..179.99,
+            course in 0u16..=360,
+            speed in 0u16..=999,
+            altitude in -99_999i32..=999_999,
+            details in any::<u8>(),
+            id in "[0-9A-F]{6}",
+        ) {
+            let report = PositionReport {
+                time_of_day: Some(time_of_day),
+                latitude,
+                longitude,
+                symbol_table: '/',
+                symbol_code: '\'',
+                course: Some(course),
+                speed: Some(speed),
+                altitude: Some(altitude),
+                comment: Comment {
+                    id: Some(IdField { details, id }),
+                    ..Default::default()
+                },
+            };
+
+            let line = encode_line("FLRDDE626>APRS,qAS,EGHL", &report);
+            let round_trip = parse_position_report(&line);
+
+            /* Coordinates are encoded with a resolution of 1/1000 minutes */
+            prop_assert!((round_trip.latitude - latitude).abs() < 1e-5, "{}", line);
+            prop_assert!((round_trip.longitude - longitude).abs() < 1e-5, "{}", line);
+            prop_assert_eq!(round_trip.time_of_day, report.time_of_day);
+            prop_assert_eq!(round_trip.course, report.course);
+            prop_assert_eq!(round_trip.speed, report.speed);
+            prop_assert_eq!(round_trip.altitude, report.altitude);
+            prop_assert_eq!(round_trip.comment.id, report.comment.id);
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use serde::Serialize;
+
+use crate::ogn::{AddressType, AircraftType};
+
+use super::{conversion::get_id_details, error::ParseError, header::PacketHeader, packet::Comment};
+
+/// Prefixes of source callsigns and the address type that they indicate
+const CALLSIGN_PREFIXES: [(&str, AddressType); 3] = [
+    ("FLR", AddressType::Flarm),
+    ("ICA", AddressType::Icao),
+    ("OGN", AddressType::Ogn),
+];
+
+/// Parsers per APRS destination call. Every source that OGN relays is identified by the
+/// destination of its packets, e.g. `ICA3D17F2>OGADSB,qAS,dl4mea:...`
+///
+/// # References
+///
+/// - [OGN Wiki](http://wiki.glidernet.org/wiki:ogn-flavoured-aprs)
+const SOURCE_PARSERS: [(&str, Source, IdentityParser); 11] = [
+    ("APRS", Source::Flarm, parse_identity_by_id_field),
+    ("OGFLR", Source::Flarm, parse_identity_by_id_field),
+    ("OGNTRK", Source::OgnTracker, parse_identity_by_id_field),
+    ("OGADSB", Source::Adsb, parse_identity_by_id_field),
+    ("OGPAW", Source::PilotAware, parse_identity_by_id_field),
+    ("OGNFNT", Source::Fanet, parse_identity_by_id_field),
+    ("OGNSKY", Source::SafeSky, parse_identity_by_id_field),
+    ("OGSPOT", Source::Spot, parse_identity_by_callsign),
+    ("OGSKYL", Source::Skylines, parse_identity_by_callsign),
+    ("OGLT24", Source::LiveTrack24, parse_identity_by_callsign),
+    ("OGCAPT", Source::Capturs, parse_identity_by_callsign),
+];
+
+/// Extracts the `Identity` of an aircraft from a parsed APRS position report
+///
+/// # Arguments
+///
+/// * `comment` - The parsed comment of the position report
+/// * `header` - The parsed header of the APRS line
+type IdentityParser = fn(comment: &Comment, header: &PacketHeader) -> Result<Identity, ParseError>;
+
+/// Network / device type that an aircraft status originates from
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    /// Default, as most statuses originate from FLARM devices
+    #[default]
+    Flarm,
+    OgnTracker,
+    Adsb,
+    PilotAware,
+    Fanet,
+    SafeSky,
+    Spot,
+    Skylines,
+    #[serde(rename = "livetrack24")]
+    LiveTrack24,
+    Capturs,
+    /// Unknown destination call, parsed like FLARM
+    Other,
+}
+
+/// Identification of an aircraft as given by a packet
+#[derive(Debug, PartialEq)]
+pub struct Identity {
+    /// Id of the aircraft
+    pub id: String,
+    /// Type of the address that the aircraft is identified by
+    pub address_type: AddressType,
+    /// Type of aircraft, if known
+    pub aircraft_type: Option<AircraftType>,
+}
+
+impl Source {
+    /// Returns the `Source` and the matching `IdentityParser` for an APRS destination call
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - The destination of the APRS packet (e.g. "OGADSB")
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let (source, _) = Source::by_destination("OGADSB");
+    /// assert_eq!(source, Source::Adsb);
+    ///
+    /// let (source, _) = Source::by_destination("OGXXXX");
+    /// assert_eq!(source, Source::Other);
+    /// ```
+    pub fn by_destination(destination: &str) -> (Source, IdentityParser) {
+        SOURCE_PARSERS
+            .iter()
+            .find(|(d, _, _)| *d == destination)
+            .map_or((Source::Other, parse_identity_by_id_field), |&(_, s, p)| {
+                (s, p)
+            })
+    }
+}
+
+/// Extracts the `Identity` from the aircraft id field "idXXYYYYYY", which is used by FLARM and
+/// most other sources. Rejects aircraft with stealth- or no-tracking-mode active.
+///
+/// # Arguments
+///
+/// * `comment` - The parsed comment of the position report
+/// * `_header` - The parsed header of the APRS line (unused)
+///
+/// # Examples
+///
+/// ```ignore
+/// let (_, comment) = parse_comment("/A=000607 id0AAB1234");
+/// let identity = parse_identity_by_id_field(&comment, &header);
+///
+/// assert!(identity.is_ok_and(|i| i.id == "AB1234"));
+/// ```
+fn parse_identity_by_id_field(
+    comment: &Comment,
+    _header: &PacketHeader,
+) -> Result<Identity, ParseError> {
+    let id_field = comment.id.as_ref().ok_or(ParseError::UnknownIdFormat)?;
+    let id_details = get_id_details(id_field.details)?;
+
+    Ok(Identity {
+        id: id_field.id.clone(),
+        address_type: id_details.address_type,
+        aircraft_type: id_details.aircraft_type,
+    })
+}
+
+/// Extracts the `Identity` from the source callsign of the header (e.g. "ICA3E7540"), which is
+/// used by sources that don't send an aircraft id field.
+///
+/// # Arguments
+///
+/// * `_comment` - The parsed comment of the position report (unused)
+/// * `header` - The parsed header of the APRS line
+///
+/// # Examples
+///
+/// ```ignore
+/// let identity = parse_identity_by_callsign(&Comment::default(), &header);
+///
+/// assert!(identity.is_ok_and(|i| i.id == "3E7540" && i.address_type == AddressType::Icao));
+/// ```
+fn parse_identity_by_callsign(
+    _comment: &Comment,
+    header: &PacketHeader,
+) -> Result<Identity, ParseError> {
+    let callsign = header.source.as_str();
+
+    let (id, address_type) = CALLSIGN_PREFIXES
+        .iter()
+        .find_map(|&(prefix, address_type)| {
+            callsign
+                .strip_prefix(prefix)
+                .filter(|id| !id.is_empty())
+                .map(|id| (id, address_type))
+        })
+        .unwrap_or((callsign, AddressType::Random));
+
+    Ok(Identity {
+        id: String::from(id),
+        address_type,
+        aircraft_type: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ogn::aprs::packet::IdField;
+
+    use super::*;
+
+    fn create_comment(details: u8, id: &str) -> Comment {
+        Comment {
+            id: Some(IdField {
+                details,
+                id: String::from(id),
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn create_header(source: &str, destination: &str) -> PacketHeader {
+        PacketHeader {
+            source: String::from(source),
+            destination: String::from(destination),
+            q_construct: Some(String::from("qAS")),
+            receiver: Some(String::from("EGHL")),
+        }
+    }
+
+    #[test]
+    fn by_destination_works() {
+        assert_eq!(Source::by_destination("APRS").0, Source::Flarm);
+        assert_eq!(Source::by_destination("OGFLR").0, Source::Flarm);
+        assert_eq!(Source::by_destination("OGADSB").0, Source::Adsb);
+        assert_eq!(Source::by_destination("OGSKYL").0, Source::Skylines);
+        assert_eq!(Source::by_destination("OGXXXX").0, Source::Other);
+    }
+
+    #[test]
+    fn parse_identity_by_id_field_works() {
+        let header = create_header("ICA3D17F2", "OGADSB");
+
+        assert_eq!(
+            parse_identity_by_id_field(&create_comment(0x25, "3D17F2"), &header),
+            Ok(Identity {
+                id: String::from("3D17F2"),
+                address_type: AddressType::Icao,
+                aircraft_type: Some(AircraftType::Jet),
+            })
+        );
+
+        assert_eq!(
+            parse_identity_by_id_field(&Comment::default(), &header),
+            Err(ParseError::UnknownIdFormat)
+        );
+
+        assert_eq!(
+            parse_identity_by_id_field(&create_comment(0x8A, "3D17F2"), &header),
+            Err(ParseError::Stealth)
+        );
+    }
+
+    #[test]
+    fn parse_identity_by_callsign_works() {
+        for (callsign, id, address_type) in [
+            ("ICA3E7540", "3E7540", AddressType::Icao),
+            ("FLRDDDD33", "DDDD33", AddressType::Flarm),
+            ("OGN123456", "123456", AddressType::Ogn),
+            ("SKY3E5906", "SKY3E5906", AddressType::Random),
+            ("ICA", "ICA", AddressType::Random),
+        ] {
+            assert_eq!(
+                parse_identity_by_callsign(&Comment::default(), &create_header(callsign, "OGSPOT")),
+                Ok(Identity {
+                    id: String::from(id),
+                    address_type,
+                    aircraft_type: None,
+                })
+            );
+        }
+    }
+}
@@ -1,11 +1,27 @@
 use std::fmt::{Display, Formatter, Result};
 
-use crate::{ogn::Aircraft, position::Position};
+use crate::{
+    ogn::{AddressType, Aircraft, AircraftId},
+    position::Position,
+};
+
+use super::{fanet::GroundState, header::PacketHeader, source::Source};
+
+/// Max. age of a status (in seconds). Older states are outdated, and everything that is kept per
+/// aircraft (e.g. FANET names) is forgotten if no further status came in within this time.
+pub const MAX_STATUS_AGE: u64 = 60 * 5; /* 5 minutes */
 
 /// Representation of an aircraft status
+#[derive(Default)]
 pub struct Status {
+    /// Header of the APRS packet, containing the receiver that heard the aircraft
+    pub header: PacketHeader,
+    /// Network / device type that the status originates from
+    pub source: Source,
     /// Affected aircraft
     pub aircraft: Aircraft,
+    /// Type of the address that the aircraft is identified by
+    pub address_type: AddressType,
     /// Position of aircraft
     pub position: Position,
     /// Speed in _km/h_
@@ -13,36 +29,118 @@ pub struct Status {
     /// Vertical speed in _m/s_
     pub vertical_speed: Option<f32>,
     /// Altitude in _m_
-    pub altitude: Option<u16>,
+    pub altitude: Option<i32>,
     /// Turn rate in _turns/min_
     pub turn_rate: Option<f32>,
     /// Course of aircraft
     pub course: Option<u16>,
+    /// Signal quality in _dB_
+    pub signal_quality: Option<f32>,
+    /// Number of bit errors that have been corrected
+    pub bit_errors: Option<u16>,
+    /// Frequency offset in _kHz_
+    pub frequency_offset: Option<f32>,
+    /// Horizontal GPS accuracy in _m_
+    pub gps_horizontal_accuracy: Option<u16>,
+    /// Vertical GPS accuracy in _m_
+    pub gps_vertical_accuracy: Option<u16>,
+    /// State of a FANET participant on the ground (e.g. landed)
+    pub ground_state: Option<GroundState>,
+    /// Timestamp of sending status (as given by the packet)
+    pub packet_time_stamp: Option<u64>,
     /// Timestamp of receiving status
     pub time_stamp: u64,
 }
 
+/// Key that identifies an aircraft across address types, as the same id may be used e.g. by an
+/// ICAO and a FLARM address.
+pub type Key = (AddressType, AircraftId);
+
+impl Status {
+    /// Returns the `Key` of the affected aircraft
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use above_me::ogn::{aprs::Status, AddressType, Aircraft};
+    /// let status = Status {
+    ///     aircraft: Aircraft {
+    ///         id: String::from("AB1234"),
+    ///         ..Default::default()
+    ///     },
+    ///     address_type: AddressType::Flarm,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(status.key(), (AddressType::Flarm, String::from("AB1234")));
+    /// ```
+    pub fn key(&self) -> Key {
+        (self.address_type, self.aircraft.id.clone())
+    }
+
+    /// Returns the timestamp that the status is valid for. That's the packet timestamp, so that
+    /// delayed packets (e.g. of a lagging server) aren't considered fresh. Falls back to the time
+    /// of receiving if the packet has no timestamp or claims to be from the future.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use above_me::ogn::aprs::Status;
+    /// let mut status = Status::default();
+    ///
+    /// status.packet_time_stamp = Some(100);
+    /// status.time_stamp = 110;
+    /// assert_eq!(status.effective_time_stamp(), 100);
+    ///
+    /// status.packet_time_stamp = None;
+    /// assert_eq!(status.effective_time_stamp(), 110);
+    /// ```
+    pub fn effective_time_stamp(&self) -> u64 {
+        self.packet_time_stamp
+            .map_or(self.time_stamp, |t| t.min(self.time_stamp))
+    }
+}
+
 impl Display for Status {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(
             f,
             "[ 
+    Header: {},
+    Source: {:?},
     Aircraft: {},
+    Address type: {:?},
     Position: {},
     Speed: {:?},
     Vertical speed: {:?},
     Altitude: {:?},
     Turn rate: {:?},
     Course: {:?},
+    Signal quality: {:?},
+    Bit errors: {:?},
+    Frequency offset: {:?},
+    GPS accuracy: {:?} x {:?},
+    Ground state: {:?},
+    Packet timestamp: {:?},
     Timestamp: {}
 ]",
+            self.header,
+            self.source,
             self.aircraft,
+            self.address_type,
             self.position,
             self.speed,
             self.vertical_speed,
             self.altitude,
             self.turn_rate,
             self.course,
+            self.signal_quality,
+            self.bit_errors,
+            self.frequency_offset,
+            self.gps_horizontal_accuracy,
+            self.gps_vertical_accuracy,
+            self.ground_state,
+            self.packet_time_stamp,
             self.time_stamp
         )
     }
@@ -0,0 +1,470 @@
+use std::{fmt::Display, io::Error, time::Duration};
+
+use futures::future::join_all;
+use log::{debug, error, info};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    join,
+    net::ToSocketAddrs,
+    sync::mpsc::{channel, Sender},
+    time::{sleep, Instant},
+};
+
+use crate::ogn::AircraftRegistry;
+
+use super::client::{connect, receive, Config, Senders, Upstream};
+use super::deduplication::Deduplicator;
+use super::fanet::Names;
+
+/// Default delay before the first reconnect (in milliseconds)
+const DEFAULT_INITIAL_DELAY_MS: u64 = 1_000;
+/// Default upper limit of the delay between two reconnects (in milliseconds)
+const DEFAULT_MAX_DELAY_MS: u64 = 1_000 * 60 * 5;
+/// Default factor that the delay grows with on every failed attempt
+const DEFAULT_MULTIPLIER: f64 = 2.0;
+/// Default ratio of the delay that is randomly added or subtracted
+const DEFAULT_JITTER: f64 = 0.25;
+/// Time a connection has to stay open to be considered healthy. Connections that get closed
+/// earlier (e.g. because the server rejected the login) count as failed attempts.
+const MIN_HEALTHY_CONNECTION_DURATION: Duration = Duration::from_secs(60);
+
+/// Configuration of the reconnect behaviour of the APRS client
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct ReconnectConfig {
+    /// Delay before the first reconnect (in milliseconds)
+    pub initial_delay_ms: u64,
+    /// Upper limit of the delay between two reconnects (in milliseconds)
+    pub max_delay_ms: u64,
+    /// Factor that the delay grows with on every failed attempt
+    pub multiplier: f64,
+    /// Ratio (0.0 - 1.0) of the delay that is randomly added or subtracted, so that multiple
+    /// clients don't reconnect at the exact same time
+    pub jitter: f64,
+    /// Number of consecutive failed attempts after which the client gives up.
+    /// Retries forever if not set.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_delay_ms: DEFAULT_INITIAL_DELAY_MS,
+            max_delay_ms: DEFAULT_MAX_DELAY_MS,
+            multiplier: DEFAULT_MULTIPLIER,
+            jitter: DEFAULT_JITTER,
+            max_attempts: None,
+        }
+    }
+}
+
+/// State of the connection to an APRS server
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ConnectionState {
+    /// Client is connecting to the server
+    Connecting,
+    /// Client is connected and receives lines
+    Connected,
+    /// Client lost its connection and waits for the next attempt
+    Reconnecting {
+        /// Number of consecutive failed attempts
+        attempt: u32,
+        /// Delay until the next attempt (in milliseconds)
+        delay_ms: u64,
+    },
+    /// Client gave up after reaching the max. number of attempts
+    Failed,
+}
+
+/// Change of the `ConnectionState` of an upstream
+pub struct ConnectionStateUpdate {
+    /// Name of the affected upstream (address and filter)
+    pub upstream: String,
+    /// New state of the connection
+    pub state: ConnectionState,
+}
+
+/// Keeps APRS clients connected to all configured upstreams. Connects to each server and
+/// (re-)connects with an exponential backoff whenever a connection fails or gets closed. Merges
+/// incoming APRS states of all upstreams, discarding duplicates and outdated states, and sends
+/// them via `senders.status_tx`. Every change of a connection state is sent via
+/// `senders.connection_state_tx`.
+///
+/// # Arguments
+///
+/// * `config` - Information on where to connect & login and how to reconnect
+/// * `senders` - The `Senders` that incoming states, receiver beacons, timestamps of incoming
+///   lines, connection states and parse errors will be sent with
+/// * `aircraft` - Mapping of `AircraftId` => `Aircraft`, necessary for conversion. May be swapped
+///   while running.
+///
+/// # Returns
+///
+/// Future that will result to () if the receiving side of `senders.status_tx` has been closed or
+/// Error if all upstreams reached the max. number of attempts.
+///
+/// # Examples
+///
+/// ```ignore
+/// use ogn::AircraftRegistry;
+/// use std::collections::HashMap;
+/// use tokio::{spawn, sync::mpsc::channel};
+///
+/// let config = aprs::Config { ... };
+/// let (status_tx, status_rx) = channel(32);
+/// let senders = aprs::Senders { status_tx, ... };
+/// let aircraft = AircraftRegistry::from_pointee(HashMap::new());
+///
+/// spawn(async move {
+///     aprs::supervise(&config, &senders, &aircraft)
+///         .await
+///         .expect("Client gave up");
+/// });
+///
+/// while let Some(status) = status_rx.recv().await {
+///     println!("Got status: {}", status);
+/// }
+/// ```
+pub async fn supervise<A: ToSocketAddrs + Display>(
+    config: &Config<A>,
+    senders: &Senders,
+    aircraft: &AircraftRegistry,
+) -> Result<(), Error> {
+    let (upstream_status_tx, mut upstream_status_rx) = channel(32);
+
+    /* Upstreams send their states to the merge instead of sending them directly. */
+    let upstream_senders = Senders {
+        status_tx: upstream_status_tx,
+        ..senders.clone()
+    };
+
+    let names = Names::default();
+
+    let upstreams = async move {
+        join_all(config.upstreams().map(|upstream| {
+            supervise_upstream(config, upstream, &upstream_senders, aircraft, &names)
+        }))
+        .await
+        /* `upstream_senders` is dropped here, so merging stops after all upstreams stopped. */
+    };
+
+    let merge = async {
+        let mut deduplicator = Deduplicator::create();
+
+        while let Some(status) = upstream_status_rx.recv().await {
+            if !deduplicator.accept(&status) {
+                debug!("Discarding duplicate or outdated status");
+                continue;
+            }
+
+            if senders.status_tx.send(status).await.is_err() {
+                /* Dropping `upstream_status_rx` stops all upstreams. */
+                break;
+            }
+        }
+
+        drop(upstream_status_rx);
+    };
+
+    let (results, ()) = join!(upstreams, merge);
+
+    if senders.status_tx.is_closed() {
+        return Ok(());
+    }
+
+    results
+        .into_iter()
+        .find(Result::is_err)
+        .unwrap_or(Err(Error::other("All upstreams stopped")))
+}
+
+/// Keeps an APRS client connected to a single upstream, see `supervise`.
+///
+/// # Arguments
+///
+/// * `config` - Information on how to login and how to reconnect
+/// * `upstream` - Information on where to connect & which filter to apply
+/// * `senders` - The `Senders` that everything received from the server will be sent with
+/// * `aircraft` - Mapping of `AircraftId` => `Aircraft`, necessary for conversion
+/// * `names` - Names of FANET aircraft, shared between all upstreams
+///
+/// # Returns
+///
+/// Future that will result to () if the receiving side of `senders.status_tx` has been closed or
+/// Error if the max. number of attempts has been reached.
+async fn supervise_upstream<A: ToSocketAddrs + Display>(
+    config: &Config<A>,
+    upstream: &Upstream<A>,
+    senders: &Senders,
+    aircraft: &AircraftRegistry,
+    names: &Names,
+) -> Result<(), Error> {
+    let name = upstream.to_string();
+    let connection_state_tx = &senders.connection_state_tx;
+    let mut attempt = 0;
+
+    loop {
+        send_state(connection_state_tx, &name, ConnectionState::Connecting).await?;
+
+        match connect(config, upstream).await {
+            Ok(tcp_stream) => {
+                info!("Client for {name} connected");
+
+                send_state(connection_state_tx, &name, ConnectionState::Connected).await?;
+
+                let connected_at = Instant::now();
+
+                match receive(tcp_stream, config, senders, aircraft, names).await {
+                    Ok(()) => info!("Client for {name} disconnected"),
+                    Err(e) => error!("Client for {name} stopped with error: {e}"),
+                }
+
+                if connected_at.elapsed() >= MIN_HEALTHY_CONNECTION_DURATION {
+                    attempt = 0;
+                }
+            }
+            Err(e) => error!("Client for {name} could not connect: {e}"),
+        }
+
+        if senders.status_tx.is_closed() {
+            /* Nobody is interested in our states anymore, so there's no need to reconnect. */
+            return Ok(());
+        }
+
+        attempt += 1;
+
+        if config
+            .reconnect
+            .max_attempts
+            .is_some_and(|max_attempts| attempt > max_attempts)
+        {
+            send_state(connection_state_tx, &name, ConnectionState::Failed).await?;
+            return Err(Error::other(format!(
+                "Giving up on {name} after {} failed attempts",
+                attempt - 1
+            )));
+        }
+
+        let delay = calculate_delay(&config.reconnect, attempt, rand::thread_rng().gen());
+
+        info!(
+            "Reconnecting to {name} in {} ms (attempt {attempt})...",
+            delay.as_millis()
+        );
+
+        send_state(
+            connection_state_tx,
+            &name,
+            ConnectionState::Reconnecting {
+                attempt,
+                delay_ms: u64::try_from(delay.as_millis()).unwrap_or(u64::MAX),
+            },
+        )
+        .await?;
+
+        sleep(delay).await;
+    }
+}
+
+/// Sends a new `ConnectionState` of an upstream via `connection_state_tx`
+///
+/// # Arguments
+///
+/// * `connection_state_tx` - The `Sender<ConnectionStateUpdate>` to send the state with
+/// * `upstream` - Name of the affected upstream
+/// * `state` - The new `ConnectionState`
+async fn send_state(
+    connection_state_tx: &Sender<ConnectionStateUpdate>,
+    upstream: &str,
+    state: ConnectionState,
+) -> Result<(), Error> {
+    connection_state_tx
+        .send(ConnectionStateUpdate {
+            upstream: String::from(upstream),
+            state,
+        })
+        .await
+        .or(Err(Error::other("Could not send connection state")))
+}
+
+/// Calculates the delay before the next reconnect
+///
+/// # Arguments
+///
+/// * `config` - The `ReconnectConfig` to calculate with
+/// * `attempt` - Number of consecutive failed attempts (starting with 1)
+/// * `random` - A random value in the range of 0.0 - 1.0 that is used for applying the jitter
+///
+/// # Examples
+///
+/// ```ignore
+/// let config = ReconnectConfig {
+///     initial_delay_ms: 1000,
+///     max_delay_ms: 10000,
+///     multiplier: 2.0,
+///     jitter: 0.0,
+///     max_attempts: None,
+/// };
+///
+/// assert_eq!(calculate_delay(&config, 1, 0.5), Duration::from_millis(1000));
+/// assert_eq!(calculate_delay(&config, 3, 0.5), Duration::from_millis(4000));
+/// ```
+fn calculate_delay(config: &ReconnectConfig, attempt: u32, random: f64) -> Duration {
+    let exponent = i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX);
+
+    /* Apply max. delay before *and* after adding the jitter. Otherwise the delay would grow to
+     * infinity (and beyond) or the jitter would take the delay above the max. delay. */
+    #[allow(clippy::cast_precision_loss)]
+    let max_delay_ms = config.max_delay_ms as f64;
+    #[allow(clippy::cast_precision_loss)]
+    let delay_ms =
+        (config.initial_delay_ms as f64 * config.multiplier.powi(exponent)).min(max_delay_ms);
+
+    let jitter = config.jitter.clamp(0.0, 1.0);
+    let factor = 1.0 - jitter + 2.0 * jitter * random.clamp(0.0, 1.0);
+
+    let delay_ms = (delay_ms * factor).clamp(0.0, max_delay_ms);
+
+    /* We check for range and also sign. */
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    Duration::from_millis(delay_ms as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{net::TcpListener, time::timeout};
+
+    use super::*;
+
+    fn create_config(jitter: f64) -> ReconnectConfig {
+        ReconnectConfig {
+            initial_delay_ms: 1_000,
+            max_delay_ms: 10_000,
+            multiplier: 2.0,
+            jitter,
+            max_attempts: None,
+        }
+    }
+
+    #[test]
+    fn calculate_delay_grows_exponentially() {
+        let config = create_config(0.0);
+
+        assert_eq!(
+            calculate_delay(&config, 1, 0.5),
+            Duration::from_millis(1_000)
+        );
+        assert_eq!(
+            calculate_delay(&config, 2, 0.5),
+            Duration::from_millis(2_000)
+        );
+        assert_eq!(
+            calculate_delay(&config, 3, 0.5),
+            Duration::from_millis(4_000)
+        );
+        assert_eq!(
+            calculate_delay(&config, 4, 0.5),
+            Duration::from_millis(8_000)
+        );
+    }
+
+    #[test]
+    fn calculate_delay_respects_max_delay() {
+        let config = create_config(0.5);
+
+        assert_eq!(
+            calculate_delay(&config, 5, 0.0),
+            Duration::from_millis(5_000)
+        );
+        assert_eq!(
+            calculate_delay(&config, 5, 1.0),
+            Duration::from_millis(10_000)
+        );
+        assert_eq!(
+            calculate_delay(&config, u32::MAX, 1.0),
+            Duration::from_millis(10_000)
+        );
+    }
+
+    #[test]
+    fn calculate_delay_applies_jitter() {
+        let config = create_config(0.25);
+
+        assert_eq!(
+            calculate_delay(&config, 2, 0.0),
+            Duration::from_millis(1_500)
+        );
+        assert_eq!(
+            calculate_delay(&config, 2, 0.5),
+            Duration::from_millis(2_000)
+        );
+        assert_eq!(
+            calculate_delay(&config, 2, 1.0),
+            Duration::from_millis(2_500)
+        );
+    }
+
+    #[tokio::test]
+    async fn supervise_upstream_gives_up_on_connections_that_get_dropped() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                drop(stream);
+            }
+        });
+
+        let config = Config {
+            upstream: Upstream {
+                address,
+                filter: None,
+            },
+            additional_upstreams: Vec::new(),
+            user_name: String::from("N0CALL"),
+            password: String::from("-1"),
+            client_id: String::from("test 0.1"),
+            reconnect: ReconnectConfig {
+                initial_delay_ms: 1,
+                max_delay_ms: 1,
+                multiplier: 1.0,
+                jitter: 0.0,
+                max_attempts: Some(2),
+            },
+            idle_timeout_seconds: 60,
+            keep_alive_interval_seconds: 60,
+        };
+
+        let (status_tx, _status_rx) = channel(32);
+        let (line_received_tx, _line_received_rx) = channel(32);
+        let (connection_state_tx, _connection_state_rx) = channel(32);
+        let (parse_error_tx, _parse_error_rx) = channel(32);
+        let (receiver_beacon_tx, _receiver_beacon_rx) = channel(32);
+
+        let senders = Senders {
+            status_tx,
+            line_received_tx,
+            connection_state_tx,
+            parse_error_tx,
+            receiver_beacon_tx,
+        };
+
+        let result = timeout(
+            Duration::from_secs(5),
+            supervise_upstream(
+                &config,
+                &config.upstream,
+                &senders,
+                &AircraftRegistry::default(),
+                &Names::default(),
+            ),
+        )
+        .await
+        .expect("supervisor should give up instead of retrying forever");
+
+        assert!(result.is_err());
+    }
+}
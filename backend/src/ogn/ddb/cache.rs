@@ -0,0 +1,74 @@
+use std::{io::Result, path::Path, time::UNIX_EPOCH};
+
+use tokio::fs;
+
+/// Extension of the temporary file that is written before replacing the cache file
+const TEMPORARY_EXTENSION: &str = "tmp";
+
+/// Stores raw DDB data in the cache file. The data is written to a temporary file first, so a
+/// failing write does not destroy the previously cached data.
+///
+/// # Arguments
+///
+/// * `path` - Path of the cache file
+/// * `ddb` - The raw DDB data
+///
+/// # Examples
+///
+/// * test `cache::store_and_load_works`
+pub async fn store(path: &Path, ddb: &str) -> Result<()> {
+    let temporary_path = path.with_extension(TEMPORARY_EXTENSION);
+
+    fs::write(&temporary_path, ddb).await?;
+    fs::rename(&temporary_path, path).await
+}
+
+/// Loads raw DDB data from the cache file
+///
+/// # Arguments
+///
+/// * `path` - Path of the cache file
+///
+/// # Returns
+///
+/// The raw DDB data and the unix timestamp of when it has been cached
+///
+/// # Examples
+///
+/// * test `cache::store_and_load_works`
+pub async fn load(path: &Path) -> Result<(String, u64)> {
+    let ddb = fs::read_to_string(path).await?;
+
+    let time_stamp = fs::metadata(path)
+        .await?
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    Ok((ddb, time_stamp))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::time::get_current_timestamp;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn store_and_load_works() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("ddb.csv");
+
+        assert!(load(&path).await.is_err());
+
+        store(&path, "first").await.unwrap();
+        store(&path, "second").await.unwrap();
+
+        let (ddb, time_stamp) = load(&path).await.unwrap();
+
+        assert_eq!(ddb, "second");
+        assert!(time_stamp.abs_diff(get_current_timestamp()) <= 5);
+        assert!(!path.with_extension(TEMPORARY_EXTENSION).exists());
+    }
+}
@@ -1,38 +1,504 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Mutex, time::Duration};
 
-use reqwest::IntoUrl;
+use log::warn;
+use reqwest::{
+    header::{HeaderValue, ACCEPT, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    StatusCode,
+};
+use tokio::time::sleep;
 
-use super::{conversion::convert, error};
+use super::{
+    conversion::{convert, convert_json, Format},
+    error::{self, Rejection, Rejections, MAX_BODY_LENGTH},
+    refresh::Config,
+};
 use crate::ogn::{Aircraft, AircraftId};
 
-const LINE_BREAK: char = '\n';
+/// Delay before the first retry of a failed request, doubled on every further retry
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// Max. delay between two retries, so that a single refresh can't take forever
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
 
-/// Fetches aircraft data from DDB
+/// HTTP client for the DDB server that sends conditional requests, so that the DDB is only
+/// downloaded again if it has changed
+pub struct Client {
+    /// Underlying HTTP client with timeouts and user agent
+    http: reqwest::Client,
+    /// The DDB server url
+    url: String,
+    /// The format that the DDB server should respond with
+    format: Format,
+    /// Number of retries of failed requests
+    retries: u32,
+    /// Delay before the first retry
+    retry_delay: Duration,
+    /// Last response that can be validated by the DDB server, if any
+    last_response: Mutex<Option<LastResponse>>,
+}
+
+/// Data of the DDB server
+pub struct Fetched {
+    /// The raw DDB data
+    pub ddb: String,
+    /// Whether the data has changed since the last request ("false" on "304 Not Modified")
+    pub modified: bool,
+}
+
+/// Response of the DDB server and its validators for conditional requests
+struct LastResponse {
+    /// Value of the "ETag" header
+    etag: Option<HeaderValue>,
+    /// Value of the "Last-Modified" header
+    last_modified: Option<HeaderValue>,
+    /// The raw DDB data
+    ddb: String,
+}
+
+impl Client {
+    /// Creates a new `Client`
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Configuration with the DDB server url, timeouts and retries
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let client = Client::create(&config).expect("Could not create DDB client");
+    /// let fetched = client.fetch().await?;
+    /// ```
+    pub fn create(config: &Config) -> Result<Client, error::Http> {
+        let http = reqwest::Client::builder()
+            .connect_timeout(config.connect_timeout)
+            .read_timeout(config.read_timeout)
+            .user_agent(create_user_agent(&config.client_id))
+            .build()
+            .map_err(error::Http::ClientError)?;
+
+        Ok(Client {
+            http,
+            url: config.url.clone(),
+            format: config.format,
+            retries: config.retries,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            last_response: Mutex::new(None),
+        })
+    }
+
+    /// Fetches the raw DDB data. If the DDB server responds with "304 Not Modified", the data of
+    /// the last response is returned. Requests that failed temporarily (e.g. on timeouts or
+    /// server errors) are retried with increasing delays.
+    ///
+    /// # Examples
+    ///
+    /// * test `client::fetch_sends_conditional_requests`
+    /// * test `client::fetch_retries_temporary_failures`
+    pub async fn fetch(&self) -> Result<Fetched, error::Http> {
+        let mut attempt = 0;
+
+        loop {
+            match self.fetch_once().await {
+                Err(e) if attempt < self.retries && is_temporary(&e) => {
+                    let delay = self.get_retry_delay(attempt);
+                    attempt += 1;
+
+                    warn!(
+                        "Fetching aircraft data failed, retrying in {} ms ({attempt}/{}): {e}",
+                        delay.as_millis(),
+                        self.retries
+                    );
+
+                    sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Returns the delay before retrying a request, doubling with every attempt up to
+    /// `MAX_RETRY_DELAY`
+    ///
+    /// # Arguments
+    ///
+    /// * `attempt` - Number of retries so far, starting at 0
+    ///
+    /// # Examples
+    ///
+    /// * test `client::get_retry_delay_is_capped`
+    fn get_retry_delay(&self, attempt: u32) -> Duration {
+        self.retry_delay
+            .saturating_mul(2_u32.saturating_pow(attempt))
+            .min(MAX_RETRY_DELAY)
+    }
+
+    /// Sends a single (conditional) request to the DDB server
+    async fn fetch_once(&self) -> Result<Fetched, error::Http> {
+        let mut request = self
+            .http
+            .get(&self.url)
+            .header(ACCEPT, self.format.media_type());
+
+        if let Some(last_response) = self
+            .last_response
+            .lock()
+            .expect("Mutex was poisoned")
+            .as_ref()
+        {
+            if let Some(etag) = &last_response.etag {
+                request = request.header(IF_NONE_MATCH, etag.clone());
+            }
+
+            if let Some(last_modified) = &last_response.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified.clone());
+            }
+        }
+
+        let response = request.send().await.map_err(error::Http::FetchError)?;
+        let status = response.status();
+
+        if status == StatusCode::NOT_MODIFIED {
+            if let Some(last_response) = self
+                .last_response
+                .lock()
+                .expect("Mutex was poisoned")
+                .as_ref()
+            {
+                return Ok(Fetched {
+                    ddb: last_response.ddb.clone(),
+                    modified: false,
+                });
+            }
+        }
+
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+
+            return Err(error::Http::StatusError {
+                status,
+                body: body.trim().chars().take(MAX_BODY_LENGTH).collect(),
+            });
+        }
+
+        let etag = response.headers().get(ETAG).cloned();
+        let last_modified = response.headers().get(LAST_MODIFIED).cloned();
+
+        let ddb = response.text().await.map_err(error::Http::ResponseError)?;
+
+        /* Without validators, the server can't tell whether the data has changed */
+        *self.last_response.lock().expect("Mutex was poisoned") =
+            if etag.is_some() || last_modified.is_some() {
+                Some(LastResponse {
+                    etag,
+                    last_modified,
+                    ddb: ddb.clone(),
+                })
+            } else {
+                None
+            };
+
+        Ok(Fetched {
+            ddb,
+            modified: true,
+        })
+    }
+}
+
+/// Creates the "User-Agent" header value from the name of the application, e.g.
+/// "my-program 0.1" => "my-program/0.1"
 ///
 /// # Arguments
 ///
-/// * `url` - The DDB server url
+/// * `client_id` - Name (and version) of the application
 ///
 /// # Examples
-/// ```
-/// let url = "https://ddb.example.com/aircraft"
-/// let aircraft = fetch_aircraft(url)
-///     .await
-///     .expect("Could not fetch DDB data");
-/// ```
-pub async fn fetch_aircraft<A: IntoUrl>(
-    url: A,
-) -> Result<HashMap<AircraftId, Aircraft>, error::Http> {
-    let response = reqwest::get(url)
-        .await
-        .map_err(|_| error::Http::FetchError)?
-        .text()
-        .await
-        .map_err(|_| error::Http::ResponseError)?;
-
-    Ok(response
-        .split(LINE_BREAK)
-        .filter_map(convert)
-        .map(|a| (a.id.clone(), a))
-        .collect::<HashMap<AircraftId, Aircraft>>())
+///
+/// * test `client::create_user_agent_works`
+fn create_user_agent(client_id: &str) -> String {
+    let user_agent = client_id
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join("/");
+
+    if user_agent.is_empty() {
+        format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+    } else {
+        user_agent
+    }
+}
+
+/// Returns whether a failed request may succeed if it is retried
+///
+/// # Arguments
+///
+/// * `error` - The error of the failed request
+fn is_temporary(error: &error::Http) -> bool {
+    match error {
+        error::Http::ClientError(_) => false,
+        error::Http::FetchError(_) | error::Http::ResponseError(_) => true,
+        error::Http::StatusError { status, .. } => {
+            status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+        }
+    }
+}
+
+/// Converts raw DDB data into a mapping of `AircraftId` => `Aircraft`. Entries that can not be
+/// converted are skipped and summarized.
+///
+/// # Arguments
+///
+/// * `ddb` - The raw DDB data
+/// * `format` - The format of `ddb`
+///
+/// # Returns
+///
+/// The mapping and a summary of the entries that have been rejected, or an error if `ddb` is not
+/// a valid export or doesn't contain a single valid aircraft.
+///
+/// # Examples
+///
+/// * test `client::parse_ddb_works`
+/// * test `client::parse_ddb_rejects_invalid_data`
+pub fn parse_ddb(
+    ddb: &str,
+    format: Format,
+) -> Result<(HashMap<AircraftId, Aircraft>, Rejections), error::Error> {
+    let entries = match format {
+        Format::Csv => ddb
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+            .map(|(i, l)| (i + 1, convert(l)))
+            .collect::<Vec<_>>(),
+        Format::Json => convert_json(ddb)
+            .map_err(error::Error::InvalidDocument)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, e)| (i + 1, e))
+            .collect::<Vec<_>>(),
+    };
+
+    let mut aircraft = HashMap::new();
+    let mut rejections = Rejections::default();
+
+    for (line, entry) in entries {
+        match entry {
+            Ok(a) => {
+                aircraft.insert(a.id.clone(), a);
+            }
+            Err(Rejection::Comment) => {}
+            Err(r) => rejections.push(line, r),
+        }
+    }
+
+    if aircraft.is_empty() {
+        return Err(error::Error::NoAircraft(rejections));
+    }
+
+    Ok((aircraft, rejections))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    use axum::{
+        extract::State,
+        http::{header, HeaderMap},
+        response::IntoResponse,
+        routing::get,
+        Router,
+    };
+    use tokio::net::TcpListener;
+
+    use super::{error::RejectedLine, *};
+
+    const DDB: &str = "'F','AB1234','ASK-21','D-6507','G1','Y','Y'";
+
+    /// Serves `router` on a random local port and returns its url
+    async fn serve(router: Router) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        format!("http://{address}/aircraft")
+    }
+
+    fn create_config(url: String) -> Config {
+        Config {
+            url,
+            format: Format::Csv,
+            client_id: String::from("test 0.1"),
+            connect_timeout: Duration::from_secs(5),
+            read_timeout: Duration::from_secs(5),
+            retries: 2,
+            refresh_interval: Duration::from_secs(3600),
+            retry_interval: Duration::from_secs(60),
+            cache_file: None,
+            overrides_file: None,
+        }
+    }
+
+    #[test]
+    fn create_user_agent_works() {
+        assert_eq!(create_user_agent("my-program 0.1"), "my-program/0.1");
+        assert_eq!(create_user_agent(" my-program "), "my-program");
+        assert_eq!(
+            create_user_agent(""),
+            format!("above_me/{}", env!("CARGO_PKG_VERSION"))
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_sends_conditional_requests() {
+        let requests = Arc::new(AtomicU32::new(0));
+
+        let handler = |State(requests): State<Arc<AtomicU32>>, headers: HeaderMap| async move {
+            requests.fetch_add(1, Ordering::Relaxed);
+
+            assert_eq!(headers[header::USER_AGENT], "test/0.1");
+            assert_eq!(headers[header::ACCEPT], "text/csv");
+
+            if headers
+                .get(header::IF_NONE_MATCH)
+                .is_some_and(|v| v == "\"v1\"")
+            {
+                return StatusCode::NOT_MODIFIED.into_response();
+            }
+
+            ([(header::ETAG, "\"v1\"")], DDB).into_response()
+        };
+
+        let url = serve(
+            Router::new()
+                .route("/aircraft", get(handler))
+                .with_state(requests.clone()),
+        )
+        .await;
+
+        let sut = Client::create(&create_config(url)).unwrap();
+
+        let fetched = sut.fetch().await.unwrap();
+        assert!(fetched.modified);
+        assert_eq!(fetched.ddb, DDB);
+
+        let fetched = sut.fetch().await.unwrap();
+        assert!(!fetched.modified);
+        assert_eq!(fetched.ddb, DDB);
+
+        assert_eq!(requests.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn get_retry_delay_is_capped() {
+        let sut = Client::create(&create_config(String::from("http://127.0.0.1:9"))).unwrap();
+
+        assert_eq!(sut.get_retry_delay(0), DEFAULT_RETRY_DELAY);
+        assert_eq!(sut.get_retry_delay(1), DEFAULT_RETRY_DELAY * 2);
+        assert_eq!(sut.get_retry_delay(3), DEFAULT_RETRY_DELAY * 8);
+        assert_eq!(sut.get_retry_delay(6), MAX_RETRY_DELAY);
+        assert_eq!(sut.get_retry_delay(20), MAX_RETRY_DELAY);
+        assert_eq!(sut.get_retry_delay(u32::MAX), MAX_RETRY_DELAY);
+    }
+
+    #[tokio::test]
+    async fn fetch_retries_temporary_failures() {
+        let requests = Arc::new(AtomicU32::new(0));
+
+        let handler = |State(requests): State<Arc<AtomicU32>>| async move {
+            match requests.fetch_add(1, Ordering::Relaxed) {
+                0 | 1 => (StatusCode::SERVICE_UNAVAILABLE, "Maintenance").into_response(),
+                2 => DDB.into_response(),
+                _ => StatusCode::NOT_FOUND.into_response(),
+            }
+        };
+
+        let url = serve(
+            Router::new()
+                .route("/aircraft", get(handler))
+                .with_state(requests.clone()),
+        )
+        .await;
+
+        let mut sut = Client::create(&create_config(url)).unwrap();
+        sut.retry_delay = Duration::from_millis(1);
+
+        let fetched = sut.fetch().await.unwrap();
+        assert!(fetched.modified);
+        assert_eq!(fetched.ddb, DDB);
+        assert_eq!(requests.load(Ordering::Relaxed), 3);
+
+        /* Client errors are not retried */
+        let error = sut.fetch().await.err().unwrap();
+        assert_eq!(error.status(), Some(StatusCode::NOT_FOUND));
+        assert_eq!(requests.load(Ordering::Relaxed), 4);
+
+        sut.retries = 0;
+        requests.store(0, Ordering::Relaxed);
+
+        let error = sut.fetch().await.err().unwrap();
+        assert_eq!(error.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(
+            error.to_string(),
+            "Unsuccessful response (503 Service Unavailable): Maintenance"
+        );
+    }
+
+    #[test]
+    fn parse_ddb_works() {
+        let ddb = "#DEVICE_TYPE,DEVICE_ID,AIRCRAFT_MODEL,REGISTRATION,CN,TRACKED,IDENTIFIED\n\
+                   'F','AB1234','ASK-21','D-6507','G1','Y','Y'\n\
+                   'F','CD5678','Unknown','','','Y','N'\n\
+                   invalid\n";
+
+        let (result, rejections) = parse_ddb(ddb, Format::Csv).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result["AB1234"].registration.as_deref(), Some("D-6507"));
+        assert!(!result["CD5678"].visible);
+        assert_eq!(rejections.count, 1);
+        assert_eq!(
+            rejections.lines,
+            vec![RejectedLine {
+                line: 4,
+                reason: Rejection::MissingFields
+            }]
+        );
+
+        let ddb = r#"{"devices":[{"device_type":"F","device_id":"AB1234","tracked":"Y","identified":"Y"},{"device_id":""}]}"#;
+        let (result, rejections) = parse_ddb(ddb, Format::Json).unwrap();
+        assert!(result.contains_key("AB1234"));
+        assert_eq!(
+            rejections.lines,
+            vec![RejectedLine {
+                line: 2,
+                reason: Rejection::MissingId
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_ddb_rejects_invalid_data() {
+        let ddb = "'F','AB1234','ASK-21','D-6507','G1','Y','Y'";
+        assert!(matches!(
+            parse_ddb(ddb, Format::Json),
+            Err(error::Error::InvalidDocument(_))
+        ));
+
+        let ddb = "#DEVICE_TYPE,DEVICE_ID\n'F','','ASK-21','D-6507','G1','Y','Y'\n";
+        let Err(error::Error::NoAircraft(rejections)) = parse_ddb(ddb, Format::Csv) else {
+            panic!("Expected no aircraft");
+        };
+        assert_eq!(rejections.count, 1);
+        assert_eq!(rejections.lines[0].line, 2);
+        assert_eq!(rejections.lines[0].reason, Rejection::MissingId);
+
+        assert!(matches!(
+            parse_ddb(r#"{"devices":[]}"#, Format::Json),
+            Err(error::Error::NoAircraft(_))
+        ));
+    }
 }
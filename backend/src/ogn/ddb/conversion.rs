@@ -1,4 +1,9 @@
-use crate::ogn::Aircraft;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::ogn::{AddressType, Aircraft, AircraftCategory, MetadataSource};
+
+use super::error::Rejection;
 
 const VALUE_YES: &str = "Y";
 const FIELD_SEPARATOR: char = ',';
@@ -7,12 +12,75 @@ const FIELD_ENCLOSURE: char = '\'';
 const EMPTY: &str = "";
 const TYPE_UNKNOWN: &str = "Unknown";
 
+const INDEX_DEVICE_TYPE: usize = 0;
 const INDEX_ID: usize = 1;
 const INDEX_TYPE: usize = 2;
 const INDEX_REGISTRATION: usize = 3;
-const INDEX_CALL_SIGN: usize = 4;
+const INDEX_COMPETITION_NUMBER: usize = 4;
 const INDEX_TRACKED: usize = 5;
 const INDEX_IDENTIFIED: usize = 6;
+/// Only part of the export if requested explicitly (e.g. "?t=1")
+const INDEX_AIRCRAFT_TYPE: usize = 7;
+
+/// Formats that the DDB can be exported in
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    /// One aircraft per line, fields enclosed by "'" and separated by ","
+    #[default]
+    Csv,
+    /// Object with a list of aircraft in field "devices"
+    Json,
+}
+
+impl Format {
+    /// Returns the media type of the `Format`, e.g. for the "Accept" header
+    ///
+    /// # Examples
+    /// ```
+    /// # use above_me::ogn::ddb::Format;
+    /// assert_eq!(Format::Json.media_type(), "application/json");
+    /// ```
+    pub fn media_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Json => "application/json",
+        }
+    }
+}
+
+/// Fields of a DDB entry, independent of the `Format`
+struct Fields<'a> {
+    device_type: &'a str,
+    id: &'a str,
+    model: &'a str,
+    registration: &'a str,
+    competition_number: &'a str,
+    tracked: &'a str,
+    identified: &'a str,
+    aircraft_type: Option<&'a str>,
+}
+
+/// Representation of an entry of the DDB JSON export
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Device {
+    device_type: String,
+    device_id: String,
+    aircraft_model: String,
+    registration: String,
+    cn: String,
+    tracked: String,
+    identified: String,
+    aircraft_type: Option<String>,
+}
+
+/// Representation of the DDB JSON export
+#[derive(Deserialize)]
+struct Document {
+    /// Entries are deserialized separately, so that a malformed entry doesn't affect the others
+    devices: Vec<Value>,
+}
 
 /// Tries converting a line of DDB into an `Aircraft` representation
 ///
@@ -20,15 +88,20 @@ const INDEX_IDENTIFIED: usize = 6;
 ///
 /// * `line` - The line that should be converted
 ///
+/// # Returns
+///
+/// The `Aircraft` or the reason why the line has been rejected
+///
 /// # Examples
 ///
 /// ```
+/// # use above_me::ogn::ddb::convert;
 /// let aircraft = convert("'O','AB1234','ASK-21','D-6507','G1','Y','Y'").unwrap();
-/// assert_eq!(aircraft.registration, "D-6507");
+/// assert_eq!(aircraft.registration.as_deref(), Some("D-6507"));
 /// ```
-pub fn convert(line: &str) -> Option<Aircraft> {
+pub fn convert(line: &str) -> Result<Aircraft, Rejection> {
     if line.starts_with(IDENTIFIER_COMMENT) {
-        return None;
+        return Err(Rejection::Comment);
     }
 
     let line = line.replace(FIELD_ENCLOSURE, EMPTY);
@@ -39,21 +112,89 @@ pub fn convert(line: &str) -> Option<Aircraft> {
         .collect::<Vec<&str>>();
 
     if fields.len() < 7 {
-        return None;
+        return Err(Rejection::MissingFields);
     }
 
-    let model = if fields[INDEX_TYPE] == TYPE_UNKNOWN {
+    convert_fields(&Fields {
+        device_type: fields[INDEX_DEVICE_TYPE],
+        id: fields[INDEX_ID],
+        model: fields[INDEX_TYPE],
+        registration: fields[INDEX_REGISTRATION],
+        competition_number: fields[INDEX_COMPETITION_NUMBER],
+        tracked: fields[INDEX_TRACKED],
+        identified: fields[INDEX_IDENTIFIED],
+        aircraft_type: fields.get(INDEX_AIRCRAFT_TYPE).copied(),
+    })
+}
+
+/// Tries converting the DDB JSON export into `Aircraft` representations
+///
+/// # Arguments
+///
+/// * `document` - The JSON export that should be converted
+///
+/// # Returns
+///
+/// Per entry the `Aircraft` or the reason why it has been rejected, or an error if `document`
+/// is not a DDB JSON export at all.
+///
+/// # Examples
+///
+/// * test `conversion::convert_json_works`
+pub fn convert_json(document: &str) -> serde_json::Result<Vec<Result<Aircraft, Rejection>>> {
+    let document = serde_json::from_str::<Document>(document)?;
+
+    Ok(document
+        .devices
+        .into_iter()
+        .map(|v| {
+            let d = serde_json::from_value::<Device>(v).map_err(|_| Rejection::InvalidEntry)?;
+
+            convert_fields(&Fields {
+                device_type: &d.device_type,
+                id: &d.device_id,
+                model: &d.aircraft_model,
+                registration: &d.registration,
+                competition_number: &d.cn,
+                tracked: &d.tracked,
+                identified: &d.identified,
+                aircraft_type: d.aircraft_type.as_deref(),
+            })
+        })
+        .collect())
+}
+
+/// Converts the `Fields` of a DDB entry into an `Aircraft` representation
+///
+/// # Arguments
+///
+/// * `fields` - The fields of the DDB entry
+///
+/// # Returns
+///
+/// The `Aircraft` or `Rejection::MissingId` if the entry doesn't contain an id
+fn convert_fields(fields: &Fields) -> Result<Aircraft, Rejection> {
+    let model = if fields.model == TYPE_UNKNOWN {
         None
     } else {
-        get_as_option(fields[INDEX_TYPE])
+        get_as_option(fields.model)
     };
 
-    Some(Aircraft {
-        id: get_as_option(fields[INDEX_ID])?,
-        call_sign: get_as_option(fields[INDEX_CALL_SIGN]),
-        registration: get_as_option(fields[INDEX_REGISTRATION]),
+    let ddb_aircraft_type = fields
+        .aircraft_type
+        .and_then(|t| t.trim().parse::<u8>().ok());
+
+    Ok(Aircraft {
+        id: get_as_option(fields.id).ok_or(Rejection::MissingId)?,
+        competition_number: get_as_option(fields.competition_number),
+        registration: get_as_option(fields.registration),
         model,
-        visible: fields[INDEX_IDENTIFIED] == VALUE_YES && fields[INDEX_TRACKED] == VALUE_YES,
+        name: None,
+        device_type: AddressType::from_ddb_str(fields.device_type),
+        category: ddb_aircraft_type.and_then(AircraftCategory::from_ddb_u8),
+        ddb_aircraft_type,
+        metadata_source: MetadataSource::Ddb,
+        visible: fields.identified == VALUE_YES && fields.tracked == VALUE_YES,
     })
 }
 
@@ -65,7 +206,7 @@ pub fn convert(line: &str) -> Option<Aircraft> {
 /// * `value` - Value that may be wrapped
 ///
 /// # Examples
-/// ```
+/// ```ignore
 /// assert!(get_as_option("").is_none());
 /// assert!(get_as_option("Value").is_some_and(|v|v == "Value"));
 /// ```
@@ -79,6 +220,8 @@ fn get_as_option(value: &str) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
+    use proptest::{collection::vec, prelude::*};
+
     use super::*;
 
     #[test]
@@ -86,14 +229,90 @@ mod tests {
         let line = "'O','AB1234','ASK-21','D-6507','G1','Y','Y'";
 
         let result = convert(line);
-        assert!(result.is_some());
+        assert!(result.is_ok());
 
         let aircraft = result.unwrap();
         assert_eq!(aircraft.id, "AB1234");
-        assert!(aircraft.call_sign.is_some_and(|v| v == "G1"));
+        assert!(aircraft.competition_number.is_some_and(|v| v == "G1"));
         assert!(aircraft.registration.is_some_and(|v| v == "D-6507"));
         assert!(aircraft.model.is_some_and(|v| v == "ASK-21"));
+        assert_eq!(aircraft.device_type, Some(AddressType::Ogn));
+        assert_eq!(aircraft.category, None);
+        assert_eq!(aircraft.ddb_aircraft_type, None);
+        assert!(aircraft.visible);
+    }
+
+    #[test]
+    fn converts_aircraft_type_correctly() {
+        let aircraft = convert("'F','AB1234','ASK-21','D-6507','G1','Y','Y','1'").unwrap();
+        assert_eq!(aircraft.device_type, Some(AddressType::Flarm));
+        assert_eq!(aircraft.category, Some(AircraftCategory::Glider));
+        assert_eq!(aircraft.ddb_aircraft_type, Some(1));
+
+        let aircraft = convert("'I','AB1234','','','','Y','Y','9'").unwrap();
+        assert_eq!(aircraft.device_type, Some(AddressType::Icao));
+        assert_eq!(aircraft.category, None);
+        assert_eq!(aircraft.ddb_aircraft_type, Some(9));
+    }
+
+    #[test]
+    fn convert_json_works() {
+        let document = r#"{"devices":[
+            {"device_type":"F","device_id":"AB1234","aircraft_model":"ASK-21","registration":"D-6507","cn":"G1","tracked":"Y","identified":"Y","aircraft_type":"1"},
+            {"device_type":"O","device_id":"CD5678","aircraft_model":"Unknown","registration":"","cn":"","tracked":"Y","identified":"N"},
+            {"device_type":"F","device_id":"","tracked":"Y","identified":"Y"},
+            {"device_type":"F","device_id":42}
+        ]}"#;
+
+        let result = convert_json(document).unwrap();
+        assert_eq!(result.len(), 4);
+        assert_eq!(result[2].as_ref().err(), Some(&Rejection::MissingId));
+        assert_eq!(result[3].as_ref().err(), Some(&Rejection::InvalidEntry));
+
+        let aircraft = result[0].as_ref().unwrap();
+        assert_eq!(aircraft.id, "AB1234");
+        assert_eq!(aircraft.competition_number.as_deref(), Some("G1"));
+        assert_eq!(aircraft.registration.as_deref(), Some("D-6507"));
+        assert_eq!(aircraft.model.as_deref(), Some("ASK-21"));
+        assert_eq!(aircraft.device_type, Some(AddressType::Flarm));
+        assert_eq!(aircraft.category, Some(AircraftCategory::Glider));
+        assert_eq!(aircraft.ddb_aircraft_type, Some(1));
         assert!(aircraft.visible);
+
+        let aircraft = result[1].as_ref().unwrap();
+        assert_eq!(aircraft.id, "CD5678");
+        assert!(aircraft.competition_number.is_none());
+        assert!(aircraft.registration.is_none());
+        assert!(aircraft.model.is_none());
+        assert_eq!(aircraft.device_type, Some(AddressType::Ogn));
+        assert!(aircraft.category.is_none());
+        assert!(!aircraft.visible);
+    }
+
+    #[test]
+    fn convert_json_rejects_other_documents() {
+        assert!(convert_json("").is_err());
+        assert!(convert_json("'F','AB1234','ASK-21','D-6507','G1','Y','Y'").is_err());
+        assert!(convert_json(r#"{"aircraft":[]}"#).is_err());
+        assert!(convert_json(r#"{"devices":[]}"#).is_ok_and(|a| a.is_empty()));
+    }
+
+    #[test]
+    fn convert_rejects_invalid_lines() {
+        assert_eq!(
+            convert("#DEVICE_TYPE,DEVICE_ID,AIRCRAFT_MODEL,REGISTRATION,CN,TRACKED,IDENTIFIED")
+                .err(),
+            Some(Rejection::Comment)
+        );
+        assert_eq!(convert("").err(), Some(Rejection::MissingFields));
+        assert_eq!(
+            convert("'F','AB1234','ASK-21'").err(),
+            Some(Rejection::MissingFields)
+        );
+        assert_eq!(
+            convert("'F','','ASK-21','D-6507','G1','Y','Y'").err(),
+            Some(Rejection::MissingId)
+        );
     }
 
     #[test]
@@ -101,11 +320,11 @@ mod tests {
         let line = "'O','AB1234','Unknown','','G1','Y','Y'";
 
         let result = convert(line);
-        assert!(result.is_some());
+        assert!(result.is_ok());
 
         let aircraft = result.unwrap();
         assert_eq!(aircraft.id, "AB1234");
-        assert!(aircraft.call_sign.is_some_and(|v| v == "G1"));
+        assert!(aircraft.competition_number.is_some_and(|v| v == "G1"));
         assert!(aircraft.registration.is_none());
         assert!(aircraft.model.is_none());
         assert!(aircraft.visible);
@@ -113,9 +332,51 @@ mod tests {
 
     #[test]
     fn sets_visible_correctly() {
-        assert!(convert("'O','AB1234','','','','Y','Y'").is_some_and(|a| a.visible));
-        assert!(convert("'O','AB1234','','','','Y','N'").is_some_and(|a| !a.visible));
-        assert!(convert("'O','AB1234','','','','N','Y'").is_some_and(|a| !a.visible));
-        assert!(convert("'O','AB1234','','','','N','N'").is_some_and(|a| !a.visible));
+        assert!(convert("'O','AB1234','','','','Y','Y'").is_ok_and(|a| a.visible));
+        assert!(convert("'O','AB1234','','','','Y','N'").is_ok_and(|a| !a.visible));
+        assert!(convert("'O','AB1234','','','','N','Y'").is_ok_and(|a| !a.visible));
+        assert!(convert("'O','AB1234','','','','N','N'").is_ok_and(|a| !a.visible));
+    }
+
+    proptest! {
+        #[test]
+        fn convert_never_panics_on_arbitrary_bytes(bytes in vec(any::<u8>(), 0..256)) {
+            let _ = convert(&String::from_utf8_lossy(&bytes));
+        }
+
+        #[test]
+        fn convert_json_never_panics_on_arbitrary_bytes(bytes in vec(any::<u8>(), 0..256)) {
+            let _ = convert_json(&String::from_utf8_lossy(&bytes));
+        }
+
+        #[test]
+        fn convert_round_trips_generated_lines(
+            id in "[0-9A-F]{6}",
+            model in "[A-Za-z0-9-]{0,12}",
+            registration in "[A-Z0-9-]{0,8}",
+            competition_number in "[A-Z0-9]{0,3}",
+            tracked in any::<bool>(),
+            identified in any::<bool>(),
+        ) {
+            let flag = |value: bool| if value { VALUE_YES } else { "N" };
+            let line = format!(
+                "'F','{id}','{model}','{registration}','{competition_number}','{}','{}'",
+                flag(tracked),
+                flag(identified)
+            );
+
+            let aircraft = convert(&line);
+            prop_assert!(aircraft.is_ok(), "{}", line);
+
+            /* Empty values are missing, so is the model "Unknown" */
+            let expected = |value: &str| (!value.is_empty() && value != "Unknown").then(|| String::from(value));
+
+            let aircraft = aircraft.unwrap();
+            prop_assert_eq!(aircraft.id, id);
+            prop_assert_eq!(aircraft.model, expected(&model));
+            prop_assert_eq!(aircraft.registration, expected(&registration));
+            prop_assert_eq!(aircraft.competition_number, expected(&competition_number));
+            prop_assert_eq!(aircraft.visible, tracked && identified);
+        }
     }
 }
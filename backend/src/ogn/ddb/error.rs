@@ -1,19 +1,252 @@
-use std::fmt::{Display, Formatter, Result};
+use std::{
+    error,
+    fmt::{Display, Formatter, Result},
+};
+
+use reqwest::StatusCode;
+use serde::Serialize;
+
+/// Max. number of rejected lines that are kept with their line number and reason
+pub const MAX_REJECTED_LINES: usize = 10;
+
+/// Max. number of characters of a response body that are kept for diagnostics
+pub const MAX_BODY_LENGTH: usize = 256;
 
 /// Enum of `Error`s for failing HTTP requests
 #[derive(Debug)]
 pub enum Http {
-    /// Could not fetch data
-    FetchError,
+    /// Could not create the HTTP client
+    ClientError(reqwest::Error),
+    /// Could not fetch data (e.g. the server is not reachable)
+    FetchError(reqwest::Error),
+    /// The server responded with an unsuccessful status
+    StatusError {
+        /// Status of the response
+        status: StatusCode,
+        /// Beginning of the response body (see `MAX_BODY_LENGTH`)
+        body: String,
+    },
     /// The response is not valid
-    ResponseError,
+    ResponseError(reqwest::Error),
+}
+
+impl Http {
+    /// Returns the HTTP status of the response, if the server responded at all
+    ///
+    /// # Examples
+    ///
+    /// * test `error::status_works`
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::ClientError(_) => None,
+            Self::FetchError(e) | Self::ResponseError(e) => e.status(),
+            Self::StatusError { status, .. } => Some(*status),
+        }
+    }
 }
 
 impl Display for Http {
     fn fmt(&self, f: &mut Formatter) -> Result {
         match self {
-            Self::FetchError => write!(f, "Could not fetch data"),
-            Self::ResponseError => write!(f, "Invalid response"),
+            Self::ClientError(e) => write!(f, "Could not create HTTP client: {e}"),
+            Self::FetchError(e) => write!(f, "Could not fetch data: {e}"),
+            Self::StatusError { status, body } if body.is_empty() => {
+                write!(f, "Unsuccessful response ({status})")
+            }
+            Self::StatusError { status, body } => {
+                write!(f, "Unsuccessful response ({status}): {body}")
+            }
+            Self::ResponseError(e) => write!(f, "Invalid response: {e}"),
+        }
+    }
+}
+
+impl error::Error for Http {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::ClientError(e) | Self::FetchError(e) | Self::ResponseError(e) => Some(e),
+            Self::StatusError { .. } => None,
+        }
+    }
+}
+
+/// Enum of `Error`s for DDB data that could not be loaded
+#[derive(Debug)]
+pub enum Error {
+    /// The DDB server could not be requested successfully
+    Http(Http),
+    /// The data is not a valid DDB JSON export
+    InvalidDocument(serde_json::Error),
+    /// The data doesn't contain a single valid aircraft
+    NoAircraft(Rejections),
+}
+
+impl Error {
+    /// Returns the HTTP status of the response, if the DDB server responded at all
+    ///
+    /// # Examples
+    ///
+    /// * test `error::status_works`
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Http(e) => e.status(),
+            Self::InvalidDocument(_) | Self::NoAircraft(_) => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Self::Http(e) => write!(f, "{e}"),
+            Self::InvalidDocument(e) => write!(f, "Invalid JSON document: {e}"),
+            Self::NoAircraft(r) => write!(f, "No valid aircraft ({r})"),
         }
     }
 }
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Http(e) => Some(e),
+            Self::InvalidDocument(e) => Some(e),
+            Self::NoAircraft(_) => None,
+        }
+    }
+}
+
+impl From<Http> for Error {
+    fn from(value: Http) -> Self {
+        Self::Http(value)
+    }
+}
+
+/// Enum of reasons why an entry of the DDB could not be converted into an `Aircraft`
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rejection {
+    /// Line is a comment (e.g. the header), so it's not an entry at all
+    Comment,
+    /// Entry doesn't contain all necessary fields
+    MissingFields,
+    /// Entry doesn't contain an aircraft id
+    MissingId,
+    /// Entry has an invalid structure (e.g. a JSON value of the wrong type)
+    InvalidEntry,
+}
+
+impl Display for Rejection {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Self::Comment => write!(f, "Comment"),
+            Self::MissingFields => write!(f, "Missing fields"),
+            Self::MissingId => write!(f, "Missing id"),
+            Self::InvalidEntry => write!(f, "Invalid entry"),
+        }
+    }
+}
+
+/// An entry of the DDB that has been rejected
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct RejectedLine {
+    /// Number of the line (CSV) or position of the entry (JSON), starting at 1
+    pub line: usize,
+    /// Reason why the entry has been rejected
+    pub reason: Rejection,
+}
+
+/// Summary of the entries of the DDB that have been rejected
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct Rejections {
+    /// Number of rejected entries
+    pub count: usize,
+    /// The first rejected entries (see `MAX_REJECTED_LINES`)
+    pub lines: Vec<RejectedLine>,
+}
+
+impl Rejections {
+    /// Adds a rejected entry to the summary
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - Number of the line or position of the entry, starting at 1
+    /// * `reason` - Reason why the entry has been rejected
+    ///
+    /// # Examples
+    ///
+    /// * test `error::rejections_works`
+    pub fn push(&mut self, line: usize, reason: Rejection) {
+        self.count += 1;
+
+        if self.lines.len() < MAX_REJECTED_LINES {
+            self.lines.push(RejectedLine { line, reason });
+        }
+    }
+
+    /// Returns whether no entry has been rejected
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+}
+
+impl Display for Rejections {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{} rejected", self.count)?;
+
+        for (index, rejected) in self.lines.iter().enumerate() {
+            let separator = if index == 0 { ": " } else { ", " };
+            write!(f, "{separator}line {} ({})", rejected.line, rejected.reason)?;
+        }
+
+        if self.count > self.lines.len() {
+            write!(f, ", ...")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejections_works() {
+        let mut sut = Rejections::default();
+        assert!(sut.is_empty());
+        assert_eq!(sut.to_string(), "0 rejected");
+
+        sut.push(3, Rejection::MissingId);
+        sut.push(5, Rejection::MissingFields);
+        assert!(!sut.is_empty());
+        assert_eq!(
+            sut.to_string(),
+            "2 rejected: line 3 (Missing id), line 5 (Missing fields)"
+        );
+
+        for line in 6..20 {
+            sut.push(line, Rejection::InvalidEntry);
+        }
+
+        assert_eq!(sut.count, 16);
+        assert_eq!(sut.lines.len(), MAX_REJECTED_LINES);
+        assert_eq!(sut.lines[0].line, 3);
+        assert!(sut.to_string().ends_with("line 13 (Invalid entry), ..."));
+    }
+
+    #[test]
+    fn status_works() {
+        let error = Error::from(Http::StatusError {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            body: String::from("Maintenance"),
+        });
+
+        assert_eq!(error.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(
+            error.to_string(),
+            "Unsuccessful response (503 Service Unavailable): Maintenance"
+        );
+
+        assert_eq!(Error::NoAircraft(Rejections::default()).status(), None);
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    io::{Error, Result},
+    path::Path,
+};
+
+use serde::Deserialize;
+use tokio::fs;
+
+use crate::ogn::{Aircraft, AircraftId, MetadataSource};
+
+const EXTENSION_JSON: &str = "json";
+const VALUE_YES: &str = "Y";
+const FIELD_SEPARATOR: char = ',';
+const IDENTIFIER_COMMENT: char = '#';
+const FIELD_ENCLOSURE: char = '\'';
+const EMPTY: &str = "";
+
+const INDEX_ID: usize = 0;
+const INDEX_COMPETITION_NUMBER: usize = 1;
+const INDEX_REGISTRATION: usize = 2;
+const INDEX_MODEL: usize = 3;
+const INDEX_HIDDEN: usize = 4;
+
+/// Locally managed values of an aircraft that take precedence over the ones of the DDB
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Override {
+    /// DDB id of the aircraft
+    pub id: AircraftId,
+    /// Competition number, e.g. "G1"
+    #[serde(alias = "call_sign")]
+    pub competition_number: Option<String>,
+    /// Registration, e.g. "D-6507"
+    pub registration: Option<String>,
+    /// Aircraft model type, e.g. "ASK-21"
+    pub model: Option<String>,
+    /// Should the aircraft be hidden, regardless of the DDB?
+    pub hidden: bool,
+}
+
+/// Loads the local overrides file. Files with the extension ".json" have to contain a list of
+/// objects with the fields of `Override`. Any other file is read as CSV with the fields
+/// `ID,COMPETITION_NUMBER,REGISTRATION,MODEL,HIDDEN` (like the DDB, values may be enclosed by "'"
+/// and lines starting with "#" are ignored).
+///
+/// # Arguments
+///
+/// * `path` - Path of the overrides file
+///
+/// # Examples
+///
+/// * test `overrides::load_works`
+pub async fn load(path: &Path) -> Result<Vec<Override>> {
+    let content = fs::read_to_string(path).await?;
+
+    match path.extension().and_then(OsStr::to_str) {
+        Some(e) if e.eq_ignore_ascii_case(EXTENSION_JSON) => {
+            serde_json::from_str(&content).map_err(Error::other)
+        }
+        _ => Ok(content.lines().filter_map(convert).collect()),
+    }
+}
+
+/// Merges `overrides` on top of `aircraft`. Values that are set in an `Override` replace the
+/// ones of the DDB, values that are not set are kept. Aircraft that are not part of the DDB are
+/// added.
+///
+/// # Arguments
+///
+/// * `aircraft` - Mapping of `AircraftId` => `Aircraft`, e.g. as fetched from the DDB
+/// * `overrides` - The overrides to apply
+///
+/// # Examples
+///
+/// * test `overrides::apply_works`
+pub fn apply(aircraft: &mut HashMap<AircraftId, Aircraft>, overrides: &[Override]) {
+    for o in overrides.iter().filter(|o| !o.id.is_empty()) {
+        let a = aircraft.entry(o.id.clone()).or_insert_with(|| Aircraft {
+            id: o.id.clone(),
+            metadata_source: MetadataSource::Override,
+            ..Default::default()
+        });
+
+        if let Some(competition_number) = get_non_empty(&o.competition_number) {
+            a.competition_number = Some(competition_number);
+        }
+
+        if let Some(registration) = get_non_empty(&o.registration) {
+            a.registration = Some(registration);
+        }
+
+        if let Some(model) = get_non_empty(&o.model) {
+            a.model = Some(model);
+        }
+
+        if o.hidden {
+            a.visible = false;
+        }
+
+        a.metadata_source = MetadataSource::Override;
+    }
+}
+
+/// Tries converting a line of the CSV overrides file into an `Override`
+///
+/// # Arguments
+///
+/// * `line` - The line that should be converted
+///
+/// # Examples
+///
+/// * test `overrides::convert_works`
+fn convert(line: &str) -> Option<Override> {
+    if line.starts_with(IDENTIFIER_COMMENT) {
+        return None;
+    }
+
+    let line = line.replace(FIELD_ENCLOSURE, EMPTY);
+
+    let fields = line
+        .split(FIELD_SEPARATOR)
+        .map(str::trim)
+        .collect::<Vec<&str>>();
+
+    let get_field = |index: usize| {
+        fields
+            .get(index)
+            .filter(|v| !v.is_empty())
+            .map(|v| String::from(*v))
+    };
+
+    Some(Override {
+        id: get_field(INDEX_ID)?,
+        competition_number: get_field(INDEX_COMPETITION_NUMBER),
+        registration: get_field(INDEX_REGISTRATION),
+        model: get_field(INDEX_MODEL),
+        hidden: fields.get(INDEX_HIDDEN) == Some(&VALUE_YES),
+    })
+}
+
+/// Returns a copy of `value`, if it is set and not empty
+///
+/// # Arguments
+///
+/// * `value` - Value that may be set
+fn get_non_empty(value: &Option<String>) -> Option<String> {
+    value.as_ref().filter(|v| !v.is_empty()).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_works() {
+        assert_eq!(
+            convert("'AB1234','G1','D-6507','ASK-21','N'"),
+            Some(Override {
+                id: String::from("AB1234"),
+                competition_number: Some(String::from("G1")),
+                registration: Some(String::from("D-6507")),
+                model: Some(String::from("ASK-21")),
+                hidden: false,
+            })
+        );
+
+        assert_eq!(
+            convert("CD5678,,,,Y"),
+            Some(Override {
+                id: String::from("CD5678"),
+                hidden: true,
+                ..Default::default()
+            })
+        );
+
+        assert_eq!(
+            convert("EF9012,,D-EXXX"),
+            Some(Override {
+                id: String::from("EF9012"),
+                registration: Some(String::from("D-EXXX")),
+                ..Default::default()
+            })
+        );
+
+        assert!(convert("#ID,COMPETITION_NUMBER,REGISTRATION,MODEL,HIDDEN").is_none());
+        assert!(convert("").is_none());
+        assert!(convert(",G1,D-6507").is_none());
+    }
+
+    #[tokio::test]
+    async fn load_works() {
+        let directory = tempfile::tempdir().unwrap();
+
+        let csv_path = directory.path().join("overrides.csv");
+        fs::write(
+            &csv_path,
+            "#ID,COMPETITION_NUMBER,REGISTRATION,MODEL,HIDDEN\nAB1234,G1,,,\n",
+        )
+        .await
+        .unwrap();
+
+        let json_path = directory.path().join("overrides.json");
+        fs::write(
+            &json_path,
+            r#"[{"id":"AB1234","call_sign":"G1"},{"id":"CD5678","hidden":true}]"#,
+        )
+        .await
+        .unwrap();
+
+        let invalid_path = directory.path().join("invalid.json");
+        fs::write(&invalid_path, "AB1234,G1,,,").await.unwrap();
+
+        let result = load(&csv_path).await.unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id, "AB1234");
+        assert_eq!(result[0].competition_number.as_deref(), Some("G1"));
+
+        let result = load(&json_path).await.unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].competition_number.as_deref(), Some("G1"));
+        assert!(!result[0].hidden);
+        assert_eq!(result[1].id, "CD5678");
+        assert!(result[1].hidden);
+
+        assert!(load(&invalid_path).await.is_err());
+        assert!(load(&directory.path().join("missing.csv")).await.is_err());
+    }
+
+    #[test]
+    fn apply_works() {
+        let mut aircraft = HashMap::from([(
+            String::from("AB1234"),
+            Aircraft {
+                id: String::from("AB1234"),
+                competition_number: Some(String::from("G1")),
+                registration: Some(String::from("D-6507")),
+                model: Some(String::from("ASK-21")),
+                metadata_source: MetadataSource::Ddb,
+                ..Default::default()
+            },
+        )]);
+
+        let overrides = [
+            Override {
+                id: String::from("AB1234"),
+                registration: Some(String::from("D-1234")),
+                model: Some(String::new()),
+                hidden: true,
+                ..Default::default()
+            },
+            Override {
+                id: String::from("CD5678"),
+                model: Some(String::from("Piper PA-25")),
+                ..Default::default()
+            },
+            Override::default(),
+        ];
+
+        apply(&mut aircraft, &overrides);
+
+        assert_eq!(aircraft.len(), 2);
+
+        let a = &aircraft["AB1234"];
+        assert_eq!(a.competition_number.as_deref(), Some("G1"));
+        assert_eq!(a.registration.as_deref(), Some("D-1234"));
+        assert_eq!(a.model.as_deref(), Some("ASK-21"));
+        assert_eq!(a.metadata_source, MetadataSource::Override);
+        assert!(!a.visible);
+
+        let a = &aircraft["CD5678"];
+        assert!(a.registration.is_none());
+        assert_eq!(a.model.as_deref(), Some("Piper PA-25"));
+        assert_eq!(a.metadata_source, MetadataSource::Override);
+        assert!(a.visible);
+    }
+}
@@ -0,0 +1,417 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+
+use log::{debug, error, info, warn};
+use tokio::{sync::mpsc::Sender, time::sleep};
+
+use crate::{
+    ogn::{Aircraft, AircraftId, AircraftRegistry},
+    time::get_current_timestamp,
+};
+
+use super::{
+    cache,
+    client::{parse_ddb, Client},
+    conversion::Format,
+    error::{Error, Rejections},
+    overrides,
+    state::{Failure, Source, State},
+};
+
+/// Configuration for loading and refreshing the aircraft data
+pub struct Config {
+    /// The DDB server url
+    pub url: String,
+    /// The format that the DDB server should respond with
+    pub format: Format,
+    /// Name (and version) of the application, used as user agent
+    pub client_id: String,
+    /// Timeout for connecting to the DDB server
+    pub connect_timeout: Duration,
+    /// Timeout for reading (a part of) the response of the DDB server
+    pub read_timeout: Duration,
+    /// Number of retries of requests that failed temporarily
+    pub retries: u32,
+    /// Time between two refreshes after the data has been fetched successfully
+    pub refresh_interval: Duration,
+    /// Time until the next attempt after fetching the data failed
+    pub retry_interval: Duration,
+    /// File that the last successfully fetched data is cached in, if any
+    pub cache_file: Option<PathBuf>,
+    /// File with local values that take precedence over the ones of the DDB, if any
+    pub overrides_file: Option<PathBuf>,
+}
+
+/// Collection of senders that `refresh_aircraft` reports to
+pub struct Senders {
+    /// Sends ids of aircraft that became invisible
+    pub hidden_tx: Sender<HashSet<AircraftId>>,
+    /// Sends the state of the aircraft data after every successful refresh
+    pub state_tx: Sender<State>,
+    /// Sends a report after every failed refresh
+    pub failure_tx: Sender<Failure>,
+}
+
+/// Loads the aircraft data, preferably from the DDB server. If the DDB server is not reachable,
+/// the data of the cache file (if configured) is used instead. The local overrides (if
+/// configured) are applied on top.
+///
+/// # Arguments
+///
+/// * `config` - Configuration for loading the aircraft data
+/// * `client` - Client for the DDB server
+///
+/// # Returns
+///
+/// The mapping of `AircraftId` => `Aircraft` and its state (`None` if the data could neither be
+/// fetched nor loaded from the cache file), and a report of why fetching from the DDB server
+/// failed (`None` if it didn't).
+///
+/// # Examples
+///
+/// * test `refresh::load_aircraft_falls_back_to_cache`
+pub async fn load_aircraft(
+    config: &Config,
+    client: &Client,
+) -> (
+    Option<(HashMap<AircraftId, Aircraft>, State)>,
+    Option<Failure>,
+) {
+    let failure = match fetch_aircraft(config, client).await {
+        Ok(result) => return (Some(result), None),
+        Err(e) => {
+            error!("Could not fetch aircraft data: {e}");
+            Failure::create(&e, get_current_timestamp())
+        }
+    };
+
+    let Some(cache_file) = &config.cache_file else {
+        return (None, Some(failure));
+    };
+
+    let (ddb, time_stamp) = match cache::load(cache_file).await {
+        Ok(c) => c,
+        Err(e) => {
+            warn!("Could not load cached aircraft data: {e}");
+            return (None, Some(failure));
+        }
+    };
+
+    let (mut aircraft, rejected) = match parse_ddb(&ddb, config.format) {
+        Ok(p) => p,
+        Err(e) => {
+            warn!("Could not convert cached aircraft data: {e}");
+            return (None, Some(failure));
+        }
+    };
+
+    log_rejections(&rejected);
+
+    apply_overrides(config, &mut aircraft).await;
+
+    let state = State {
+        source: Source::Cache,
+        time_stamp,
+        count: aircraft.len(),
+        rejected,
+    };
+
+    (Some((aircraft, state)), Some(failure))
+}
+
+/// Periodically refetches the DDB and swaps the result into `aircraft`. Ids of aircraft that
+/// became invisible are sent via `senders.hidden_tx`, so states that have already been stored
+/// for them can be purged. If fetching fails, the current mapping is kept and fetching is retried
+/// after `config.retry_interval`.
+///
+/// # Arguments
+///
+/// * `config` - Configuration for refreshing the aircraft data
+/// * `client` - Client for the DDB server
+/// * `aircraft` - The mapping of `AircraftId` => `Aircraft` that gets swapped on every refresh
+/// * `senders` - Senders for updates of the aircraft data
+/// * `state` - State of the current aircraft data, `None` if there is none
+///
+/// # Returns
+///
+/// Future that will result to () if a receiving side of `senders` has been closed.
+///
+/// # Examples
+///
+/// ```ignore
+/// let client = Client::create(&config)?;
+/// let (aircraft, state) = load_aircraft(&config, &client).await.0.unzip();
+/// let aircraft = AircraftRegistry::from_pointee(aircraft.unwrap_or_default());
+///
+/// spawn(async move {
+///     refresh_aircraft(&config, &client, &aircraft, &senders, state.as_ref()).await;
+/// });
+/// ```
+pub async fn refresh_aircraft(
+    config: &Config,
+    client: &Client,
+    aircraft: &AircraftRegistry,
+    senders: &Senders,
+    state: Option<&State>,
+) {
+    /* Data that has not been fetched just now (e.g. cached data) should be replaced soon */
+    let mut delay = match state {
+        Some(State {
+            source: Source::Network,
+            ..
+        }) => config.refresh_interval,
+        _ => config.retry_interval,
+    };
+
+    loop {
+        sleep(delay).await;
+
+        let (current, state) = match fetch_aircraft(config, client).await {
+            Ok(result) => result,
+            Err(e) => {
+                warn!(
+                    "Could not refresh aircraft data, retrying in {} seconds: {e}",
+                    config.retry_interval.as_secs()
+                );
+                delay = config.retry_interval;
+
+                let failure = Failure::create(&e, get_current_timestamp());
+                if senders.failure_tx.send(failure).await.is_err() {
+                    return;
+                }
+
+                continue;
+            }
+        };
+
+        delay = config.refresh_interval;
+
+        let hidden_ids = get_hidden_ids(&aircraft.load(), &current);
+        info!("Refreshed aircraft data ({} aircraft)", current.len());
+
+        /* The aircraft data is replaced before the hidden aircraft are purged, so that statuses
+         * that were converted with the previous data (and are still queued) can be discarded
+         * by checking the current data when storing them. */
+        aircraft.store(Arc::new(current));
+
+        if senders.state_tx.send(state).await.is_err() {
+            return;
+        }
+
+        if hidden_ids.is_empty() {
+            continue;
+        }
+
+        debug!("{} aircraft became invisible", hidden_ids.len());
+
+        if senders.hidden_tx.send(hidden_ids).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Fetches the aircraft data from the DDB server, stores it in the cache file (if configured)
+/// and applies the local overrides (if configured). A response without any valid aircraft is
+/// treated as an error, so that it can't replace usable data. If the data has not changed since
+/// the last request, it is converted again anyway, so that changed overrides are applied.
+///
+/// # Arguments
+///
+/// * `config` - Configuration for fetching the aircraft data
+/// * `client` - Client for the DDB server
+async fn fetch_aircraft(
+    config: &Config,
+    client: &Client,
+) -> Result<(HashMap<AircraftId, Aircraft>, State), Error> {
+    let fetched = client.fetch().await?;
+    let (mut aircraft, rejected) = parse_ddb(&fetched.ddb, config.format)?;
+
+    if fetched.modified {
+        log_rejections(&rejected);
+
+        if let Some(cache_file) = &config.cache_file {
+            if let Err(e) = cache::store(cache_file, &fetched.ddb).await {
+                warn!("Could not cache aircraft data: {e}");
+            }
+        }
+    } else {
+        debug!("Aircraft data has not changed");
+    }
+
+    apply_overrides(config, &mut aircraft).await;
+
+    let state = State {
+        source: Source::Network,
+        time_stamp: get_current_timestamp(),
+        count: aircraft.len(),
+        rejected,
+    };
+
+    Ok((aircraft, state))
+}
+
+/// Logs a summary of the rejected entries of the DDB, if there are any
+///
+/// # Arguments
+///
+/// * `rejections` - Summary of the rejected entries
+fn log_rejections(rejections: &Rejections) {
+    if !rejections.is_empty() {
+        warn!("Skipped invalid entries of aircraft data ({rejections})");
+    }
+}
+
+/// Applies the local overrides file (if configured) on top of `aircraft`. The file is read on
+/// every call, so changes are picked up by the next refresh. If it can't be read, `aircraft` is
+/// kept as it is.
+///
+/// # Arguments
+///
+/// * `config` - Configuration with the path of the overrides file
+/// * `aircraft` - Mapping of `AircraftId` => `Aircraft` that the overrides are applied to
+///
+/// # Examples
+///
+/// * test `refresh::load_aircraft_falls_back_to_cache`
+pub async fn apply_overrides(config: &Config, aircraft: &mut HashMap<AircraftId, Aircraft>) {
+    let Some(overrides_file) = &config.overrides_file else {
+        return;
+    };
+
+    match overrides::load(overrides_file).await {
+        Ok(o) => overrides::apply(aircraft, &o),
+        Err(e) => warn!("Could not load aircraft overrides: {e}"),
+    }
+}
+
+/// Returns the ids of aircraft that are invisible in the `current` mapping but were visible (or
+/// unknown, which is treated as visible) in the `previous` one
+///
+/// # Arguments
+///
+/// * `previous` - Mapping of `AircraftId` => `Aircraft` before the refresh
+/// * `current` - Mapping of `AircraftId` => `Aircraft` after the refresh
+///
+/// # Examples
+///
+/// * test `refresh::get_hidden_ids_works`
+fn get_hidden_ids(
+    previous: &HashMap<AircraftId, Aircraft>,
+    current: &HashMap<AircraftId, Aircraft>,
+) -> HashSet<AircraftId> {
+    current
+        .values()
+        .filter(|a| !a.visible)
+        .filter(|a| previous.get(&a.id).is_none_or(|p| p.visible))
+        .map(|a| a.id.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use crate::ogn::MetadataSource;
+
+    use super::*;
+
+    fn create_aircraft(id: &str, visible: bool) -> (AircraftId, Aircraft) {
+        (
+            String::from(id),
+            Aircraft {
+                id: String::from(id),
+                metadata_source: MetadataSource::Ddb,
+                visible,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn load_aircraft_falls_back_to_cache() {
+        let directory = tempfile::tempdir().unwrap();
+        let cache_file = directory.path().join("ddb.csv");
+
+        /* Nothing listens on the port once the listener is dropped, so fetching fails
+         * immediately */
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+
+        let mut config = Config {
+            url: format!("http://{address}/aircraft"),
+            format: Format::Csv,
+            client_id: String::from("test 0.1"),
+            connect_timeout: Duration::from_secs(5),
+            read_timeout: Duration::from_secs(5),
+            retries: 0,
+            refresh_interval: Duration::from_secs(3600),
+            retry_interval: Duration::from_secs(60),
+            cache_file: None,
+            overrides_file: None,
+        };
+        let client = Client::create(&config).unwrap();
+
+        let (loaded, failure) = load_aircraft(&config, &client).await;
+        assert!(loaded.is_none());
+        assert!(failure.is_some_and(|f| f.status.is_none()));
+
+        config.cache_file = Some(cache_file.clone());
+        let (loaded, failure) = load_aircraft(&config, &client).await;
+        assert!(loaded.is_none());
+        assert!(failure.is_some());
+
+        cache::store(&cache_file, "'F','AB1234','ASK-21','D-6507','G1','Y','Y'")
+            .await
+            .unwrap();
+
+        let (loaded, failure) = load_aircraft(&config, &client).await;
+        let (aircraft, state) = loaded.unwrap();
+
+        assert!(failure.is_some());
+        assert_eq!(aircraft.len(), 1);
+        assert!(aircraft.contains_key("AB1234"));
+        assert_eq!(state.source, Source::Cache);
+        assert_eq!(state.count, 1);
+        assert!(state.time_stamp.abs_diff(get_current_timestamp()) <= 5);
+
+        let overrides_file = directory.path().join("overrides.csv");
+        tokio::fs::write(&overrides_file, "AB1234,,,,Y\nCD5678,G2,,,")
+            .await
+            .unwrap();
+        config.overrides_file = Some(overrides_file);
+
+        let (aircraft, state) = load_aircraft(&config, &client).await.0.unwrap();
+
+        assert_eq!(state.count, 2);
+        assert!(!aircraft["AB1234"].visible);
+        assert_eq!(aircraft["AB1234"].metadata_source, MetadataSource::Override);
+        assert_eq!(aircraft["CD5678"].competition_number.as_deref(), Some("G2"));
+    }
+
+    #[test]
+    fn get_hidden_ids_works() {
+        let previous = HashMap::from([
+            create_aircraft("AAAAAA", true),
+            create_aircraft("BBBBBB", true),
+            create_aircraft("CCCCCC", false),
+            create_aircraft("DDDDDD", true),
+        ]);
+
+        let current = HashMap::from([
+            create_aircraft("AAAAAA", true),
+            create_aircraft("BBBBBB", false),
+            create_aircraft("CCCCCC", false),
+            create_aircraft("EEEEEE", false),
+        ]);
+
+        assert_eq!(
+            get_hidden_ids(&previous, &current),
+            HashSet::from([String::from("BBBBBB"), String::from("EEEEEE")])
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use std::error::Error as _;
+
+use serde::Serialize;
+
+use super::error::{Error, Rejections};
+
+/// Origin of the aircraft data that is currently in use
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    /// Data has been fetched from the DDB server
+    Network,
+    /// Data has been loaded from the cache file as the DDB server was not reachable
+    Cache,
+}
+
+/// State of the aircraft data that is currently in use
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct State {
+    /// Origin of the data
+    pub source: Source,
+    /// Unix timestamp of when the data has been fetched from the DDB server
+    pub time_stamp: u64,
+    /// Number of known aircraft
+    pub count: usize,
+    /// Summary of the entries of the data that have been rejected
+    pub rejected: Rejections,
+}
+
+/// Report of a failed attempt to fetch the aircraft data from the DDB server
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Failure {
+    /// Unix timestamp of the attempt
+    pub time_stamp: u64,
+    /// HTTP status of the response, if the DDB server responded at all
+    pub status: Option<u16>,
+    /// Description of the error, including its underlying errors
+    pub message: String,
+}
+
+impl Failure {
+    /// Creates a `Failure` report for an `Error`
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The error that occurred while fetching
+    /// * `time_stamp` - Unix timestamp of the attempt
+    ///
+    /// # Examples
+    ///
+    /// * test `state::create_works`
+    pub fn create(error: &Error, time_stamp: u64) -> Failure {
+        let mut message = error.to_string();
+        let mut source = error.source();
+
+        /* Errors usually include the message of their direct source already */
+        while let Some(s) = source {
+            let description = s.to_string();
+
+            if !message.contains(&description) {
+                message.push_str(": ");
+                message.push_str(&description);
+            }
+
+            source = s.source();
+        }
+
+        Failure {
+            time_stamp,
+            status: error.status().map(|s| s.as_u16()),
+            message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::StatusCode;
+
+    use super::{super::error::Http, *};
+
+    #[test]
+    fn create_works() {
+        let error = Error::from(Http::StatusError {
+            status: StatusCode::NOT_FOUND,
+            body: String::new(),
+        });
+
+        assert_eq!(
+            Failure::create(&error, 42),
+            Failure {
+                time_stamp: 42,
+                status: Some(404),
+                message: String::from("Unsuccessful response (404 Not Found)"),
+            }
+        );
+
+        let error = Error::InvalidDocument(serde_json::from_str::<u8>("x").unwrap_err());
+        let failure = Failure::create(&error, 42);
+
+        assert_eq!(failure.status, None);
+        assert_eq!(
+            failure.message,
+            "Invalid JSON document: expected value at line 1 column 1"
+        );
+    }
+}
@@ -3,19 +3,54 @@ mod aircraft;
 pub mod aprs {
     mod client;
     mod conversion;
+    mod deduplication;
+    mod error;
+    mod fanet;
+    mod header;
+    mod packet;
+    mod source;
     mod status;
+    mod supervisor;
 
-    pub use client::{init, Config};
-    pub use status::Status;
+    pub use client::{Config, Senders};
+    pub use conversion::convert;
+    pub(crate) use conversion::convert_as_i32;
+    pub use error::ParseError;
+    pub use fanet::GroundState;
+    pub(crate) use packet::{parse_packet, Body as PacketBody};
+    pub use source::Source;
+    pub use status::{Key as StatusKey, Status, MAX_STATUS_AGE};
+    pub use supervisor::{supervise, ConnectionState};
 }
 pub mod ddb {
+    mod cache;
     mod client;
     mod conversion;
     mod error;
+    mod overrides;
+    mod refresh;
+    mod state;
+
+    pub use client::{parse_ddb, Client, Fetched};
+    pub use conversion::{convert, convert_json, Format};
+    pub use error::{Error, Http as HttpError, RejectedLine, Rejection, Rejections};
+    pub use overrides::Override;
+    pub use refresh::{apply_overrides, load_aircraft, refresh_aircraft, Config, Senders};
+    pub use state::{Failure, Source, State};
+}
+pub mod receiver {
+    mod beacon;
+    mod conversion;
 
-    pub use client::fetch_aircraft;
+    pub use beacon::{Beacon, Receiver};
+    pub use conversion::convert;
 }
 
+pub use aircraft::find as find_aircraft;
+pub use aircraft::AddressType;
 pub use aircraft::Aircraft;
+pub use aircraft::Category as AircraftCategory;
 pub use aircraft::Id as AircraftId;
+pub use aircraft::MetadataSource;
+pub use aircraft::Registry as AircraftRegistry;
 pub use aircraft::Type as AircraftType;
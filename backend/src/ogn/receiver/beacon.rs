@@ -0,0 +1,210 @@
+use serde::Serialize;
+
+use crate::position::Position;
+
+/// Representation of a beacon sent by an OGN receiver (ground station)
+pub enum Beacon {
+    /// Position beacon, e.g. `LFNW>OGNSDR,TCPIP*,qAC,GLIDERN5:/101722h4321.05NI00128.68E&/A=000738`
+    Position(PositionBeacon),
+    /// Status beacon, e.g. `LFNW>OGNSDR,TCPIP*,qAC,GLIDERN5:>101722h v0.2.8.RPI-GPU CPU:0.5 ...`
+    Status(StatusBeacon),
+}
+
+/// Position of a receiver as given by its position beacon
+pub struct PositionBeacon {
+    /// Name of the receiver
+    pub receiver_id: String,
+    /// Position of the receiver
+    pub position: Position,
+    /// Altitude in _m_
+    pub altitude: Option<i32>,
+    /// Timestamp of receiving the beacon
+    pub time_stamp: u64,
+}
+
+/// System information of a receiver as given by its status beacon
+pub struct StatusBeacon {
+    /// Name of the receiver
+    pub receiver_id: String,
+    /// Version of the receiver software
+    pub version: Option<String>,
+    /// Platform that the receiver software runs on (e.g. "RPI-GPU")
+    pub platform: Option<String>,
+    /// CPU load
+    pub cpu_load: Option<f32>,
+    /// Free RAM in _MB_
+    pub ram_free: Option<f32>,
+    /// Total RAM in _MB_
+    pub ram_total: Option<f32>,
+    /// NTP offset in _ms_
+    pub ntp_offset: Option<f32>,
+    /// NTP frequency correction in _ppm_
+    pub ntp_correction: Option<f32>,
+    /// CPU temperature in _°C_
+    pub cpu_temperature: Option<f32>,
+    /// Timestamp of receiving the beacon
+    pub time_stamp: u64,
+}
+
+/// Representation of a receiver, combining the latest position and status beacon
+#[derive(Clone, Serialize)]
+pub struct Receiver {
+    /// Name of the receiver
+    pub id: String,
+    /// Position of the receiver, if a position beacon has been received yet
+    pub position: Option<Position>,
+    /// Altitude in _m_
+    pub altitude: Option<i32>,
+    /// Version of the receiver software
+    pub version: Option<String>,
+    /// Platform that the receiver software runs on (e.g. "RPI-GPU")
+    pub platform: Option<String>,
+    /// CPU load
+    pub cpu_load: Option<f32>,
+    /// Free RAM in _MB_
+    pub ram_free: Option<f32>,
+    /// Total RAM in _MB_
+    pub ram_total: Option<f32>,
+    /// NTP offset in _ms_
+    pub ntp_offset: Option<f32>,
+    /// NTP frequency correction in _ppm_
+    pub ntp_correction: Option<f32>,
+    /// CPU temperature in _°C_
+    pub cpu_temperature: Option<f32>,
+    /// Timestamp of receiving the latest position beacon
+    pub last_position_update: Option<u64>,
+    /// Timestamp of receiving the latest status beacon
+    pub last_status_update: Option<u64>,
+}
+
+impl Beacon {
+    /// Returns the name of the receiver that sent the beacon
+    pub fn receiver_id(&self) -> &str {
+        match self {
+            Beacon::Position(beacon) => &beacon.receiver_id,
+            Beacon::Status(beacon) => &beacon.receiver_id,
+        }
+    }
+}
+
+impl Receiver {
+    /// Creates a new `Receiver` without any information yet
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Name of the receiver
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use above_me::ogn::receiver::Receiver;
+    /// let receiver = Receiver::create(String::from("LFNW"));
+    /// ```
+    pub fn create(id: String) -> Receiver {
+        Receiver {
+            id,
+            position: None,
+            altitude: None,
+            version: None,
+            platform: None,
+            cpu_load: None,
+            ram_free: None,
+            ram_total: None,
+            ntp_offset: None,
+            ntp_correction: None,
+            cpu_temperature: None,
+            last_position_update: None,
+            last_status_update: None,
+        }
+    }
+
+    /// Updates the receiver with the information of a beacon. A position beacon only updates the
+    /// position values, a status beacon only updates the system information.
+    ///
+    /// # Arguments
+    ///
+    /// * `beacon` - The beacon that the receiver sent
+    ///
+    /// # Examples
+    ///
+    /// * test `beacon::update_keeps_values_of_other_beacon`
+    pub fn update(&mut self, beacon: Beacon) {
+        match beacon {
+            Beacon::Position(beacon) => {
+                self.position = Some(beacon.position);
+                self.altitude = beacon.altitude;
+                self.last_position_update = Some(beacon.time_stamp);
+            }
+            Beacon::Status(beacon) => {
+                self.version = beacon.version;
+                self.platform = beacon.platform;
+                self.cpu_load = beacon.cpu_load;
+                self.ram_free = beacon.ram_free;
+                self.ram_total = beacon.ram_total;
+                self.ntp_offset = beacon.ntp_offset;
+                self.ntp_correction = beacon.ntp_correction;
+                self.cpu_temperature = beacon.cpu_temperature;
+                self.last_status_update = Some(beacon.time_stamp);
+            }
+        }
+    }
+
+    /// Returns the timestamp of receiving the latest beacon
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use above_me::ogn::receiver::Receiver;
+    /// let mut receiver = Receiver::create(String::from("LFNW"));
+    ///
+    /// receiver.last_position_update = Some(100);
+    /// receiver.last_status_update = Some(110);
+    /// assert_eq!(receiver.last_update(), 110);
+    /// ```
+    pub fn last_update(&self) -> u64 {
+        self.last_position_update
+            .max(self.last_status_update)
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_keeps_values_of_other_beacon() {
+        let mut sut = Receiver::create(String::from("LFNW"));
+
+        sut.update(Beacon::Position(PositionBeacon {
+            receiver_id: String::from("LFNW"),
+            position: Position {
+                latitude: 43.35083,
+                longitude: 1.47800,
+            },
+            altitude: Some(225),
+            time_stamp: 100,
+        }));
+
+        sut.update(Beacon::Status(StatusBeacon {
+            receiver_id: String::from("LFNW"),
+            version: Some(String::from("0.2.8")),
+            platform: Some(String::from("RPI-GPU")),
+            cpu_load: Some(0.5),
+            ram_free: None,
+            ram_total: None,
+            ntp_offset: None,
+            ntp_correction: None,
+            cpu_temperature: None,
+            time_stamp: 110,
+        }));
+
+        assert!(sut.position.is_some());
+        assert_eq!(sut.altitude, Some(225));
+        assert_eq!(sut.version.as_deref(), Some("0.2.8"));
+        assert_eq!(sut.cpu_load, Some(0.5));
+        assert_eq!(sut.last_position_update, Some(100));
+        assert_eq!(sut.last_status_update, Some(110));
+        assert_eq!(sut.last_update(), 110);
+    }
+}
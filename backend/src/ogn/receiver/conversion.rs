@@ -0,0 +1,210 @@
+use std::sync::LazyLock;
+
+use regex::{Captures, Regex};
+
+use crate::{
+    ogn::aprs::{convert_as_i32, parse_packet, PacketBody},
+    position::Position,
+    time::get_current_timestamp,
+};
+
+use super::beacon::{Beacon, PositionBeacon, StatusBeacon};
+
+/// Destination call of receiver beacons
+const DESTINATION_RECEIVER: &str = "OGNSDR";
+/// Regex pattern to extract data from the text of receiver status beacons
+///
+/// # Notes
+///
+/// All values are optional as not every receiver software sends all of them. Texts without any
+/// of them (e.g. free text of the operator) are no status beacons, see `has_known_values`.
+const STATUS_PATTERN: &str = r"^(?:v(?<version>\d+\.\d+\.\d+)(?:\.(?<platform>[A-Za-z0-9_-]+))?)?(?: ?CPU:(?<cpuLoad>[0-9.]+))?(?: RAM:(?<ramFree>[0-9.]+)/(?<ramTotal>[0-9.]+)MB)?(?: NTP:(?<ntpOffset>[-+0-9.]+)ms/(?<ntpCorrection>[-+0-9.]+)ppm)?(?: (?<cpuTemperature>[-+][0-9.]+)C)?";
+
+/// Factor to convert ft to m
+const FACTOR_FT_TO_M: f64 = 0.3048;
+
+static STATUS_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(STATUS_PATTERN).unwrap());
+
+/// Tries converting an APRS line of an OGN receiver into a `Beacon`
+///
+/// # Arguments
+///
+/// * `line` - The APRS line
+///
+/// # Returns
+///
+/// `Beacon` if the line is a position or status beacon of a receiver, `None` otherwise
+///
+/// # Examples
+///
+/// ```
+/// # use above_me::ogn::receiver::convert;
+/// let line = "LFNW>OGNSDR,TCPIP*,qAC,GLIDERN5:/101722h4321.05NI00128.68E&/A=000738";
+///
+/// assert!(convert(line).is_some_and(|b| b.receiver_id() == "LFNW"));
+/// ```
+pub fn convert(line: &str) -> Option<Beacon> {
+    let packet = parse_packet(line).ok()?;
+
+    if packet.header.destination != DESTINATION_RECEIVER {
+        return None;
+    }
+
+    let receiver_id = packet.header.source;
+
+    match packet.body {
+        PacketBody::Position(report) => Some(Beacon::Position(PositionBeacon {
+            receiver_id,
+            position: Position {
+                latitude: report.latitude,
+                longitude: report.longitude,
+            },
+            altitude: report
+                .altitude
+                .and_then(|a| convert_as_i32(a, FACTOR_FT_TO_M)),
+            time_stamp: get_current_timestamp(),
+        })),
+        PacketBody::Status(report) => STATUS_REGEX
+            .captures(&report.text)
+            .filter(has_known_values)
+            .map(|captures| Beacon::Status(convert_status_beacon(receiver_id, &captures))),
+        PacketBody::Other(_) => None,
+    }
+}
+
+/// Converts the captures of a receiver status beacon into a `StatusBeacon`
+///
+/// # Arguments
+///
+/// * `receiver_id` - Id of the receiver that sent the beacon
+/// * `captures` - Captures of `STATUS_REGEX`
+fn convert_status_beacon(receiver_id: String, captures: &Captures) -> StatusBeacon {
+    StatusBeacon {
+        receiver_id,
+        version: captures.name("version").map(|m| m.as_str().to_string()),
+        platform: captures.name("platform").map(|m| m.as_str().to_string()),
+        cpu_load: capture_as_f32(captures, "cpuLoad"),
+        ram_free: capture_as_f32(captures, "ramFree"),
+        ram_total: capture_as_f32(captures, "ramTotal"),
+        ntp_offset: capture_as_f32(captures, "ntpOffset"),
+        ntp_correction: capture_as_f32(captures, "ntpCorrection"),
+        cpu_temperature: capture_as_f32(captures, "cpuTemperature"),
+        time_stamp: get_current_timestamp(),
+    }
+}
+
+/// Checks whether a status text contains at least one known value. As all groups of
+/// `STATUS_PATTERN` are optional, it matches any text, which would otherwise reset all known
+/// values of the receiver.
+///
+/// # Arguments
+///
+/// * `captures` - Captures of `STATUS_REGEX`
+fn has_known_values(captures: &Captures) -> bool {
+    /* The first group is the whole (possibly empty) match. */
+    captures.iter().skip(1).any(|m| m.is_some())
+}
+
+/// Tries converting a `Captures` value to `f32`
+///
+/// # Arguments
+///
+/// * `captures` - The regex `Captures` to look up
+/// * `name` - Name of the captured value that should be converted
+///
+/// # Examples
+///
+/// ```ignore
+/// let captures = Regex::new(r"(?<value>[\d.]+)")
+///     .unwrap()
+///     .captures("12.34")
+///     .unwrap();
+///
+/// assert!(capture_as_f32(&captures, "value").is_some_and(|f| f == 12.34));
+/// ```
+fn capture_as_f32(captures: &Captures, name: &str) -> Option<f32> {
+    captures.name(name)?.as_str().parse::<f32>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_parses_position_beacon() {
+        let line = "LFNW>OGNSDR,TCPIP*,qAC,GLIDERN5:/101722h4321.05NI00128.68E&/A=000738";
+
+        let Some(Beacon::Position(beacon)) = convert(line) else {
+            panic!("Expected position beacon");
+        };
+
+        assert_eq!(beacon.receiver_id, "LFNW");
+        assert_eq!(beacon.position.latitude, 43.350833333333334);
+        assert_eq!(beacon.position.longitude, 1.4780000000000002);
+        assert_eq!(beacon.altitude, Some(225));
+        assert!(beacon.time_stamp > 0);
+    }
+
+    #[test]
+    fn convert_parses_status_beacon() {
+        let line = "LFNW>OGNSDR,TCPIP*,qAC,GLIDERN5:>101722h v0.2.8.RPI-GPU CPU:0.5 RAM:761.4/968.2MB NTP:0.3ms/-5.9ppm +54.2C 1/1Acfts[1h] RF:+69+0.8ppm/+3.53dB/+2.0dB@10km[1071]/+13.6dB@48km[5]";
+
+        let Some(Beacon::Status(beacon)) = convert(line) else {
+            panic!("Expected status beacon");
+        };
+
+        assert_eq!(beacon.receiver_id, "LFNW");
+        assert_eq!(beacon.version.as_deref(), Some("0.2.8"));
+        assert_eq!(beacon.platform.as_deref(), Some("RPI-GPU"));
+        assert_eq!(beacon.cpu_load, Some(0.5));
+        assert_eq!(beacon.ram_free, Some(761.4));
+        assert_eq!(beacon.ram_total, Some(968.2));
+        assert_eq!(beacon.ntp_offset, Some(0.3));
+        assert_eq!(beacon.ntp_correction, Some(-5.9));
+        assert_eq!(beacon.cpu_temperature, Some(54.2));
+    }
+
+    #[test]
+    fn convert_parses_incomplete_status_beacon() {
+        let line = "LFNW>OGNSDR,TCPIP*,qAC,GLIDERN5:>101722h v0.2.7.arm CPU:1.2";
+
+        let Some(Beacon::Status(beacon)) = convert(line) else {
+            panic!("Expected status beacon");
+        };
+
+        assert_eq!(beacon.version.as_deref(), Some("0.2.7"));
+        assert_eq!(beacon.platform.as_deref(), Some("arm"));
+        assert_eq!(beacon.cpu_load, Some(1.2));
+        assert_eq!(beacon.ram_free, None);
+        assert_eq!(beacon.cpu_temperature, None);
+    }
+
+    #[test]
+    fn convert_ignores_status_beacon_without_known_values() {
+        let line = "LFNW>OGNSDR,TCPIP*,qAC,GLIDERN5:>101722h Antenna on the hangar roof";
+
+        assert!(convert(line).is_none());
+    }
+
+    #[test]
+    fn capture_as_f32_works() {
+        let captures = Regex::new(r"(?<value>[\d.]+)")
+            .unwrap()
+            .captures("12.34")
+            .unwrap();
+
+        assert!(capture_as_f32(&captures, "value").is_some_and(|f| f == 12.34));
+        assert!(capture_as_f32(&captures, "missing").is_none());
+    }
+
+    #[test]
+    fn convert_ignores_other_lines() {
+        for line in [
+            "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 id0ADDE626 -019fpm +0.0rot",
+            "GLIDERN5>OGNSDR,TCPIP*,qAC,GLIDERN5:some unknown message",
+            "# aprsc 2.1.10-gd72b0a5",
+        ] {
+            assert!(convert(line).is_none());
+        }
+    }
+}
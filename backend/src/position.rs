@@ -1,16 +1,27 @@
-use std::fmt::{Display, Formatter, Result};
+use std::{
+    f64::consts::FRAC_PI_2,
+    fmt::{Display, Formatter, Result},
+};
 
 use serde::Serialize;
 
-const EARTH_MEAN_RADIUS_KM: f32 = 6371.0;
+mod grid;
+
+pub use grid::Grid;
+
+const EARTH_MEAN_RADIUS_KM: f64 = 6371.0;
+const MIN_LATITUDE: f64 = -90.0;
+const MAX_LATITUDE: f64 = 90.0;
+const MIN_LONGITUDE: f64 = -180.0;
+const MAX_LONGITUDE: f64 = 180.0;
 
 /// Representation of a position
-#[derive(Clone, Serialize)]
+#[derive(Clone, Default, Serialize)]
 pub struct Position {
     /// Latitude
-    pub latitude: f32,
+    pub latitude: f64,
     /// Longitude
-    pub longitude: f32,
+    pub longitude: f64,
 }
 
 /// Calculates the distance of two given positions in km.
@@ -22,7 +33,7 @@ pub struct Position {
 ///
 /// # Examples
 /// ```
-/// use aprs::Position
+/// use above_me::position::{calculate_distance, Position};
 ///
 /// let pos1 = Position {
 ///    latitude: 48.858222,
@@ -34,15 +45,15 @@ pub struct Position {
 ///    longitude: 2.3376,
 /// };
 ///
-/// assert_eq!(calculate_distance(&pos1, &pos2), 3.1636212);
-/// assert_eq!(calculate_distance(&pos2, &pos1), 3.1636212);
+/// assert_eq!(calculate_distance(&pos1, &pos2), 3.1636392286614017);
+/// assert_eq!(calculate_distance(&pos2, &pos1), 3.1636392286614017);
 /// ```
 ///
 /// # Resources
 ///
 /// * [www.geeksforgeeks.org](https://www.geeksforgeeks.org/haversine-formula-to-find-distance-between-two-points-on-a-sphere/)
 /// * [www.movable-type.co.uk](https://www.movable-type.co.uk/scripts/latlong.html)
-pub fn calculate_distance(pos1: &Position, pos2: &Position) -> f32 {
+pub fn calculate_distance(pos1: &Position, pos2: &Position) -> f64 {
     let delta_latitude = (pos1.latitude - pos2.latitude).to_radians();
     let delta_longitude = (pos1.longitude - pos2.longitude).to_radians();
 
@@ -57,6 +68,92 @@ pub fn calculate_distance(pos1: &Position, pos2: &Position) -> f32 {
     c * EARTH_MEAN_RADIUS_KM
 }
 
+/// Representation of an area between two latitudes and two longitudes. To describe an area that
+/// crosses the antimeridian, `min_longitude` may be less than -180 or `max_longitude` may be
+/// greater than 180.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoundingBox {
+    /// Southern latitude
+    pub min_latitude: f64,
+    /// Northern latitude
+    pub max_latitude: f64,
+    /// Western longitude
+    pub min_longitude: f64,
+    /// Eastern longitude
+    pub max_longitude: f64,
+}
+
+impl BoundingBox {
+    /// Returns the smallest `BoundingBox` that contains all positions within `range` around
+    /// `position`. If the range includes a pole, the box covers all longitudes. The latitude of
+    /// `position` is clamped to -90 to 90 and its longitude is wrapped into -180 to 180 first.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Center of the area
+    /// * `range` - Range around `position` in km
+    ///
+    /// # Examples
+    ///
+    /// * test `position::bounding_box_around_works`
+    /// * test `position::bounding_box_around_includes_poles`
+    ///
+    /// # Resources
+    ///
+    /// * [janmatuschek.de](http://janmatuschek.de/LatitudeLongitudeBoundingCoordinates)
+    pub fn around(position: &Position, range: f64) -> BoundingBox {
+        let latitude = position.latitude.clamp(MIN_LATITUDE, MAX_LATITUDE);
+        let longitude = wrap_longitude(position.longitude);
+
+        let angular_range = range / EARTH_MEAN_RADIUS_KM;
+        let delta_latitude = angular_range.to_degrees();
+
+        let min_latitude = latitude - delta_latitude;
+        let max_latitude = latitude + delta_latitude;
+
+        if min_latitude <= MIN_LATITUDE
+            || max_latitude >= MAX_LATITUDE
+            || angular_range >= FRAC_PI_2
+        {
+            return BoundingBox {
+                min_latitude: min_latitude.max(MIN_LATITUDE),
+                max_latitude: max_latitude.min(MAX_LATITUDE),
+                min_longitude: MIN_LONGITUDE,
+                max_longitude: MAX_LONGITUDE,
+            };
+        }
+
+        let delta_longitude = (angular_range.sin() / latitude.to_radians().cos())
+            .asin()
+            .to_degrees();
+
+        BoundingBox {
+            min_latitude,
+            max_latitude,
+            min_longitude: longitude - delta_longitude,
+            max_longitude: longitude + delta_longitude,
+        }
+    }
+}
+
+/// Wraps `longitude` into -180 to 180 (e.g. 190 becomes -170). Non-finite values stay
+/// non-finite.
+///
+/// # Arguments
+///
+/// * `longitude` - The longitude to wrap
+///
+/// # Examples
+///
+/// * test `position::wrap_longitude_works`
+fn wrap_longitude(longitude: f64) -> f64 {
+    if (MIN_LONGITUDE..=MAX_LONGITUDE).contains(&longitude) {
+        return longitude;
+    }
+
+    (longitude - MIN_LONGITUDE).rem_euclid(MAX_LONGITUDE - MIN_LONGITUDE) + MIN_LONGITUDE
+}
+
 impl Display for Position {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(
@@ -84,7 +181,75 @@ mod tests {
         };
 
         /* This value matches online calculators, so I assume it's correct */
-        assert_eq!(calculate_distance(&pos1, &pos2), 3.1636212);
-        assert_eq!(calculate_distance(&pos2, &pos1), 3.1636212);
+        assert_eq!(calculate_distance(&pos1, &pos2), 3.1636392286614017);
+        assert_eq!(calculate_distance(&pos2, &pos1), 3.1636392286614017);
+    }
+
+    #[test]
+    fn bounding_box_around_works() {
+        let position = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        let result = BoundingBox::around(&position, 10.0);
+
+        for (latitude, longitude) in [
+            (result.min_latitude, position.longitude),
+            (result.max_latitude, position.longitude),
+        ] {
+            let distance = calculate_distance(
+                &position,
+                &Position {
+                    latitude,
+                    longitude,
+                },
+            );
+            assert!((distance - 10.0).abs() < 0.001);
+        }
+
+        assert!(result.min_longitude < position.longitude - 0.13);
+        assert!(result.max_longitude > position.longitude + 0.13);
+        assert!(result.max_longitude < position.longitude + 0.14);
+
+        let result = BoundingBox::around(
+            &Position {
+                latitude: 0.0,
+                longitude: 179.95,
+            },
+            20.0,
+        );
+
+        assert!(result.max_longitude > MAX_LONGITUDE);
+    }
+
+    #[test]
+    fn wrap_longitude_works() {
+        assert_eq!(wrap_longitude(2.2945), 2.2945);
+        assert_eq!(wrap_longitude(180.0), 180.0);
+        assert_eq!(wrap_longitude(190.0), -170.0);
+        assert_eq!(wrap_longitude(-540.0), -180.0);
+        assert!((-180.0..=180.0).contains(&wrap_longitude(-1e12)));
+        assert!(wrap_longitude(f64::NAN).is_nan());
+        assert!(wrap_longitude(f64::INFINITY).is_nan());
+    }
+
+    #[test]
+    fn bounding_box_around_includes_poles() {
+        let position = Position {
+            latitude: 89.5,
+            longitude: 2.2945,
+        };
+
+        let result = BoundingBox::around(&position, 100.0);
+
+        assert_eq!(result.max_latitude, MAX_LATITUDE);
+        assert_eq!(result.min_longitude, MIN_LONGITUDE);
+        assert_eq!(result.max_longitude, MAX_LONGITUDE);
+
+        let result = BoundingBox::around(&position, f64::INFINITY);
+
+        assert_eq!(result.min_latitude, MIN_LATITUDE);
+        assert_eq!(result.max_latitude, MAX_LATITUDE);
     }
 }
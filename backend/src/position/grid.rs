@@ -0,0 +1,430 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use super::{wrap_longitude, BoundingBox, Position, MIN_LATITUDE, MIN_LONGITUDE};
+
+const LATITUDE_SPAN: f64 = 180.0;
+const LONGITUDE_SPAN: f64 = 360.0;
+
+/// Cell of a `Grid`, identified by its row (latitude) and column (longitude)
+type Cell = (i32, i32);
+
+/// Spatial index that sorts keys into cells of equal size (in degrees), so that queries only
+/// have to look at the keys in the cells that intersect the queried area
+///
+/// # Examples
+///
+/// * test `grid::query_works`
+pub struct Grid<K> {
+    /// Size of a cell in degrees
+    cell_size: f64,
+    /// Number of rows (latitude)
+    rows: i32,
+    /// Number of columns (longitude)
+    columns: i32,
+    /// Keys per occupied cell
+    cells: HashMap<Cell, HashSet<K>>,
+    /// Cell per key
+    keys: HashMap<K, Cell>,
+}
+
+impl<K: Clone + Eq + Hash> Grid<K> {
+    /// Creates a new, empty `Grid`
+    ///
+    /// # Arguments
+    ///
+    /// * `cell_size` - Size of a cell in degrees, should divide 180 without remainder
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use above_me::position::Grid;
+    ///
+    /// let grid = Grid::<String>::create(1.0);
+    /// ```
+    pub fn create(cell_size: f64) -> Grid<K> {
+        Grid {
+            cell_size,
+            rows: (LATITUDE_SPAN / cell_size).ceil() as i32,
+            columns: (LONGITUDE_SPAN / cell_size).ceil() as i32,
+            cells: HashMap::new(),
+            keys: HashMap::new(),
+        }
+    }
+
+    /// Stores `key` at given `position`. If `key` is already stored, it's moved.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to store
+    /// * `position` - Current position of `key`
+    ///
+    /// # Examples
+    ///
+    /// * test `grid::insert_moves_keys`
+    pub fn insert(&mut self, key: K, position: &Position) {
+        let cell = self.get_cell(position);
+
+        match self.keys.insert(key.clone(), cell) {
+            Some(previous) if previous == cell => return,
+            Some(previous) => self.remove_from_cell(&key, previous),
+            None => {}
+        }
+
+        self.cells.entry(cell).or_default().insert(key);
+    }
+
+    /// Removes `key` from the `Grid`, if it's stored
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to remove
+    ///
+    /// # Examples
+    ///
+    /// * test `grid::insert_moves_keys`
+    pub fn remove(&mut self, key: &K) {
+        if let Some(cell) = self.keys.remove(key) {
+            self.remove_from_cell(key, cell);
+        }
+    }
+
+    /// Returns the keys in all cells that intersect `bounding_box`. As whole cells are returned,
+    /// keys slightly outside of `bounding_box` may be included. Longitudes are wrapped around the
+    /// globe; non-finite longitudes are treated like the whole globe.
+    ///
+    /// # Arguments
+    ///
+    /// * `bounding_box` - The area to search in
+    ///
+    /// # Examples
+    ///
+    /// * test `grid::query_works`
+    /// * test `grid::query_wraps_around_antimeridian`
+    pub fn query<'a>(&'a self, bounding_box: &BoundingBox) -> impl Iterator<Item = &'a K> + 'a {
+        let min_row = self.get_row(bounding_box.min_latitude);
+        let max_row = self.get_row(bounding_box.max_latitude);
+
+        let longitude_span = bounding_box.max_longitude - bounding_box.min_longitude;
+
+        /* Shifts the area so that its western longitude is within -180 to 180, which keeps the
+         * columns small enough to never overflow. Visits every column only once, even if the
+         * area wraps around the whole globe. */
+        let (min_column, max_column) = match longitude_span {
+            span if span < LONGITUDE_SPAN => {
+                let min_longitude = wrap_longitude(bounding_box.min_longitude);
+                let min_column = self.get_unwrapped_column(min_longitude);
+                let max_column = self.get_unwrapped_column(min_longitude + span);
+
+                (min_column, max_column.min(min_column + self.columns - 1))
+            }
+            _ => (0, self.columns - 1),
+        };
+
+        let columns = self.columns;
+        let contains = move |&(row, column): &Cell| {
+            (min_row..=max_row).contains(&row)
+                && (column - min_column).rem_euclid(columns) <= max_column - min_column
+        };
+
+        /* Large areas (e.g. the whole world) contain more cells than are occupied, so it's
+         * cheaper to check every occupied cell than to look up every cell of the area. */
+        let area = i64::from(max_row - min_row + 1) * i64::from(max_column - min_column + 1);
+
+        let cells: Box<dyn Iterator<Item = &HashSet<K>>> = if area > self.cells.len() as i64 {
+            Box::new(
+                self.cells
+                    .iter()
+                    .filter(move |(cell, _)| contains(cell))
+                    .map(|(_, keys)| keys),
+            )
+        } else {
+            Box::new((min_row..=max_row).flat_map(move |row| {
+                (min_column..=max_column)
+                    .filter_map(move |column| self.cells.get(&(row, column.rem_euclid(columns))))
+            }))
+        };
+
+        cells.flatten()
+    }
+
+    /// Returns the number of stored keys
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns whether no key is stored
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Removes `key` from `cell`, dropping the cell if it's empty afterwards
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to remove
+    /// * `cell` - The cell that contains `key`
+    fn remove_from_cell(&mut self, key: &K, cell: Cell) {
+        if let Some(keys) = self.cells.get_mut(&cell) {
+            keys.remove(key);
+
+            if keys.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+
+    /// Returns the cell that contains `position`
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The position to get the cell of
+    fn get_cell(&self, position: &Position) -> Cell {
+        (
+            self.get_row(position.latitude),
+            self.get_unwrapped_column(wrap_longitude(position.longitude))
+                .rem_euclid(self.columns),
+        )
+    }
+
+    /// Returns the row that contains `latitude`, clamped to the valid rows
+    ///
+    /// # Arguments
+    ///
+    /// * `latitude` - The latitude to get the row of
+    fn get_row(&self, latitude: f64) -> i32 {
+        (((latitude - MIN_LATITUDE) / self.cell_size).floor() as i32).clamp(0, self.rows - 1)
+    }
+
+    /// Returns the column that contains `longitude`, which may be outside of the valid columns
+    /// if `longitude` is outside of -180 to 180 (`longitude` should be wrapped beforehand, see
+    /// `wrap_longitude`)
+    ///
+    /// # Arguments
+    ///
+    /// * `longitude` - The longitude to get the column of
+    fn get_unwrapped_column(&self, longitude: f64) -> i32 {
+        ((longitude - MIN_LONGITUDE) / self.cell_size).floor() as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_moves_keys() {
+        let mut sut = Grid::create(1.0);
+        let position = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        sut.insert("AAAAAA", &position);
+        sut.insert("BBBBBB", &position);
+        sut.insert(
+            "AAAAAA",
+            &Position {
+                latitude: -33.8568,
+                longitude: 151.2153,
+            },
+        );
+
+        assert_eq!(sut.len(), 2);
+        assert_eq!(sut.cells.len(), 2);
+
+        sut.remove(&"BBBBBB");
+        sut.remove(&"CCCCCC");
+
+        assert_eq!(sut.len(), 1);
+        assert_eq!(sut.cells.len(), 1);
+        assert_eq!(sut.query(&BoundingBox::around(&position, 100.0)).count(), 0);
+
+        sut.remove(&"AAAAAA");
+
+        assert!(sut.is_empty());
+        assert!(sut.cells.is_empty());
+    }
+
+    #[test]
+    fn query_works() {
+        let mut sut = Grid::create(1.0);
+        let position = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        sut.insert("AAAAAA", &position);
+        sut.insert(
+            "BBBBBB",
+            &Position {
+                latitude: 48.86055,
+                longitude: 2.3376,
+            },
+        );
+        sut.insert(
+            "CCCCCC",
+            &Position {
+                latitude: 43.35083,
+                longitude: 1.478,
+            },
+        );
+        sut.insert(
+            "DDDDDD",
+            &Position {
+                latitude: 90.0,
+                longitude: 180.0,
+            },
+        );
+
+        let mut result = sut
+            .query(&BoundingBox::around(&position, 10.0))
+            .collect::<Vec<_>>();
+        result.sort_unstable();
+
+        assert_eq!(result, [&"AAAAAA", &"BBBBBB"]);
+
+        /* Larger areas check every occupied cell instead of looking up every cell */
+        assert_eq!(
+            sut.query(&BoundingBox::around(&position, 1000.0)).count(),
+            3
+        );
+        assert_eq!(
+            sut.query(&BoundingBox::around(&position, f64::INFINITY))
+                .count(),
+            4
+        );
+    }
+
+    #[test]
+    fn query_wraps_around_antimeridian() {
+        let mut sut = Grid::create(1.0);
+
+        sut.insert(
+            "AAAAAA",
+            &Position {
+                latitude: -17.75,
+                longitude: -179.95,
+            },
+        );
+        sut.insert(
+            "BBBBBB",
+            &Position {
+                latitude: -17.75,
+                longitude: 179.95,
+            },
+        );
+        sut.insert(
+            "CCCCCC",
+            &Position {
+                latitude: -17.75,
+                longitude: 0.0,
+            },
+        );
+
+        let mut result = sut
+            .query(&BoundingBox::around(
+                &Position {
+                    latitude: -17.75,
+                    longitude: 179.95,
+                },
+                20.0,
+            ))
+            .collect::<Vec<_>>();
+        result.sort_unstable();
+
+        assert_eq!(result, [&"AAAAAA", &"BBBBBB"]);
+    }
+
+    #[test]
+    fn query_handles_invalid_areas() {
+        let mut sut = Grid::create(1.0);
+
+        sut.insert(
+            "AAAAAA",
+            &Position {
+                latitude: 0.0,
+                longitude: -1e12,
+            },
+        );
+        sut.insert(
+            "BBBBBB",
+            &Position {
+                latitude: f64::NAN,
+                longitude: f64::INFINITY,
+            },
+        );
+        sut.insert(
+            "CCCCCC",
+            &Position {
+                latitude: 48.858222,
+                longitude: 362.2945,
+            },
+        );
+
+        let position = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        /* Same as around 2.2945 */
+        let mut result = sut
+            .query(&BoundingBox::around(
+                &Position {
+                    latitude: 48.858222,
+                    longitude: 722.2945,
+                },
+                10.0,
+            ))
+            .collect::<Vec<_>>();
+        result.sort_unstable();
+        assert_eq!(result, [&"CCCCCC"]);
+
+        for (latitude, longitude, range) in [
+            (0.0, -1e12, 5000.0),
+            (0.0, 1e12, 5000.0),
+            (-1e12, 0.0, 5000.0),
+            (f64::NAN, 0.0, 100.0),
+            (0.0, f64::NAN, 100.0),
+            (0.0, f64::INFINITY, 100.0),
+            (0.0, 0.0, f64::NAN),
+            (0.0, 0.0, -100.0),
+            (0.0, 0.0, f64::INFINITY),
+        ] {
+            /* Must neither panic nor return more keys than stored */
+            let result = sut.query(&BoundingBox::around(
+                &Position {
+                    latitude,
+                    longitude,
+                },
+                range,
+            ));
+            assert!(result.count() <= sut.len());
+        }
+
+        for bounding_box in [
+            BoundingBox {
+                min_latitude: 0.0,
+                max_latitude: 1.0,
+                min_longitude: -1e12,
+                max_longitude: -1e12 + 1.0,
+            },
+            BoundingBox {
+                min_latitude: 0.0,
+                max_latitude: 1.0,
+                min_longitude: f64::NEG_INFINITY,
+                max_longitude: f64::INFINITY,
+            },
+        ] {
+            assert!(sut.query(&bounding_box).count() <= sut.len());
+        }
+
+        assert_eq!(
+            sut.query(&BoundingBox::around(&position, f64::INFINITY))
+                .count(),
+            3
+        );
+    }
+}
@@ -0,0 +1,466 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    hash::{BuildHasher, RandomState},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        RwLock,
+    },
+};
+
+use crate::{
+    ogn::aprs::{Status, StatusKey},
+    position::{calculate_distance, BoundingBox, Grid, Position},
+    time::get_current_timestamp,
+};
+
+/// Size of the cells of the spatial index in degrees (about 111 km in latitude)
+const GRID_CELL_SIZE: f64 = 1.0;
+/// Number of independently locked parts of the `Store`
+const SHARD_COUNT: usize = 16;
+
+/// Concurrent storage of the current states, indexed by their position. The states are split
+/// into shards by their key, each behind its own `RwLock`, so that readers only ever lock one
+/// shard at a time and never block storing states in the other shards. Outdated states are not
+/// returned, but only removed by `Store::remove_outdated`, which looks at the expired states
+/// only.
+pub struct Store {
+    /// Independently locked parts of the states
+    shards: Vec<RwLock<Shard>>,
+    /// Hasher to choose the shard of a key
+    hasher: RandomState,
+    /// Max. age of a status in seconds (see `Status::effective_time_stamp`)
+    max_age: u64,
+    /// Number of states in all shards, including outdated ones that haven't been removed yet
+    len: AtomicUsize,
+    /// Latest timestamp of receiving a status, 0 if none has been stored yet
+    last_update: AtomicU64,
+}
+
+/// Part of the states of a `Store`
+struct Shard {
+    /// Currently stored states
+    states: HashMap<StatusKey, Status>,
+    /// Spatial index of the keys of `states`
+    grid: Grid<StatusKey>,
+    /// Keys by the effective timestamp of their status, oldest first. Every key of `states` is
+    /// listed exactly once, it's moved if its status is updated.
+    expiry_queue: BTreeMap<u64, HashSet<StatusKey>>,
+}
+
+impl Store {
+    /// Creates a new, empty `Store`
+    ///
+    /// # Arguments
+    ///
+    /// * `max_age` - Max. age of a status in seconds, older states are outdated
+    pub fn create(max_age: u64) -> Store {
+        Store {
+            shards: (0..SHARD_COUNT)
+                .map(|_| {
+                    RwLock::new(Shard {
+                        states: HashMap::new(),
+                        grid: Grid::create(GRID_CELL_SIZE),
+                        expiry_queue: BTreeMap::new(),
+                    })
+                })
+                .collect(),
+            hasher: RandomState::new(),
+            max_age,
+            len: AtomicUsize::new(0),
+            last_update: AtomicU64::new(0),
+        }
+    }
+
+    /// Stores / updates a status
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status to store / update
+    ///
+    /// # Examples
+    ///
+    /// * test `store::near_works`
+    /// * test `store::insert_keeps_one_expiry_entry_per_key`
+    pub fn insert(&self, status: Status) {
+        let key = status.key();
+        let time_stamp = status.effective_time_stamp();
+
+        self.last_update
+            .fetch_max(status.time_stamp, Ordering::Relaxed);
+
+        let mut shard = self.get_shard(&key).write().expect("RwLock was poisoned");
+        let Shard {
+            states,
+            grid,
+            expiry_queue,
+        } = &mut *shard;
+
+        grid.insert(key.clone(), &status.position);
+
+        if let Some(previous) = states.insert(key.clone(), status) {
+            let previous_time_stamp = previous.effective_time_stamp();
+
+            if previous_time_stamp == time_stamp {
+                return;
+            }
+
+            dequeue(expiry_queue, &key, previous_time_stamp);
+        } else {
+            self.len.fetch_add(1, Ordering::Relaxed);
+        }
+
+        expiry_queue.entry(time_stamp).or_default().insert(key);
+    }
+
+    /// Removes all outdated states. Only the expired entries of the expiry queues are looked at.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of removed states
+    ///
+    /// # Examples
+    ///
+    /// * test `store::remove_outdated_works`
+    pub fn remove_outdated(&self) -> usize {
+        let current_timestamp = get_current_timestamp();
+        let mut count = 0;
+
+        for shard in &self.shards {
+            let mut shard = shard.write().expect("RwLock was poisoned");
+            let Shard {
+                states,
+                grid,
+                expiry_queue,
+            } = &mut *shard;
+
+            while let Some(entry) = expiry_queue.first_entry() {
+                if current_timestamp.saturating_sub(*entry.key()) <= self.max_age {
+                    break;
+                }
+
+                for key in entry.remove() {
+                    states.remove(&key);
+                    grid.remove(&key);
+                    count += 1;
+                }
+            }
+        }
+
+        self.len.fetch_sub(count, Ordering::Relaxed);
+
+        count
+    }
+
+    /// Keeps only the states that match `predicate`. Looks at all states, so it should only be
+    /// used rarely.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - Returns whether a status should be kept
+    ///
+    /// # Examples
+    ///
+    /// * test `store::retain_works`
+    pub fn retain(&self, mut predicate: impl FnMut(&Status) -> bool) {
+        for shard in &self.shards {
+            let mut shard = shard.write().expect("RwLock was poisoned");
+            let Shard {
+                states,
+                grid,
+                expiry_queue,
+            } = &mut *shard;
+            let previous_len = states.len();
+
+            states.retain(|key, status| {
+                let keep = predicate(status);
+
+                if !keep {
+                    grid.remove(key);
+                    dequeue(expiry_queue, key, status.effective_time_stamp());
+                }
+
+                keep
+            });
+
+            self.len
+                .fetch_sub(previous_len - states.len(), Ordering::Relaxed);
+        }
+    }
+
+    /// Returns the mapped states within `range` around `position`. Only the states in the cells
+    /// around `position` are looked at, outdated states are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The position that should be searched for
+    /// * `range` - Range around given `position` in km
+    /// * `map` - Maps a status and its distance to `position`, leaving it out if `None`
+    ///
+    /// # Examples
+    ///
+    /// * test `store::near_works`
+    pub fn near<T>(
+        &self,
+        position: &Position,
+        range: f64,
+        mut map: impl FnMut(&Status, f64) -> Option<T>,
+    ) -> Vec<T> {
+        let current_timestamp = get_current_timestamp();
+        let bounding_box = BoundingBox::around(position, range);
+        let mut result = Vec::new();
+
+        for shard in &self.shards {
+            let shard = shard.read().expect("RwLock was poisoned");
+
+            result.extend(
+                shard
+                    .grid
+                    .query(&bounding_box)
+                    .filter_map(|key| shard.states.get(key))
+                    .filter(|status| !self.is_outdated(status, current_timestamp))
+                    .map(|status| (status, calculate_distance(position, &status.position)))
+                    .filter(|&(_, distance)| distance <= range)
+                    .filter_map(|(status, distance)| map(status, distance)),
+            );
+        }
+
+        result
+    }
+
+    /// Returns the number of stored states in constant time. Outdated states are counted until
+    /// they are removed by `Store::remove_outdated`.
+    ///
+    /// # Examples
+    ///
+    /// * test `store::remove_outdated_works`
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Returns whether no states are stored, see `Store::len`
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the latest timestamp of receiving a status, if any states are stored. The
+    /// timestamp is kept if the latest status is removed while older ones are still stored.
+    ///
+    /// # Examples
+    ///
+    /// * test `store::remove_outdated_works`
+    pub fn last_update(&self) -> Option<u64> {
+        if self.is_empty() {
+            return None;
+        }
+
+        match self.last_update.load(Ordering::Relaxed) {
+            0 => None,
+            v => Some(v),
+        }
+    }
+
+    /// Returns the shard that `key` belongs to
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key of a status
+    fn get_shard(&self, key: &StatusKey) -> &RwLock<Shard> {
+        &self.shards[self.hasher.hash_one(key) as usize % SHARD_COUNT]
+    }
+
+    /// Checks whether `status` is outdated
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status to check
+    /// * `current_timestamp` - The current timestamp
+    fn is_outdated(&self, status: &Status, current_timestamp: u64) -> bool {
+        current_timestamp.saturating_sub(status.effective_time_stamp()) > self.max_age
+    }
+}
+
+/// Removes `key` from the entry of `time_stamp` of an expiry queue, dropping the entry if it's
+/// empty afterwards
+///
+/// # Arguments
+///
+/// * `expiry_queue` - The expiry queue of a `Shard`
+/// * `key` - The key to remove
+/// * `time_stamp` - Effective timestamp of the status of `key`
+fn dequeue(expiry_queue: &mut BTreeMap<u64, HashSet<StatusKey>>, key: &StatusKey, time_stamp: u64) {
+    if let Some(keys) = expiry_queue.get_mut(&time_stamp) {
+        keys.remove(key);
+
+        if keys.is_empty() {
+            expiry_queue.remove(&time_stamp);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ogn::Aircraft;
+
+    use super::*;
+
+    const MAX_AGE: u64 = 60;
+
+    #[test]
+    fn near_works() {
+        let sut = Store::create(MAX_AGE);
+        let current_timestamp = get_current_timestamp();
+
+        sut.insert(create_status(
+            "AAAAAA",
+            48.858222,
+            2.2945,
+            current_timestamp,
+        ));
+        sut.insert(create_status("BBBBBB", 48.86055, 2.3376, current_timestamp));
+        sut.insert(create_status("CCCCCC", 43.35083, 1.478, current_timestamp));
+        sut.insert(create_status("CCCCCC", 48.86055, 2.3376, current_timestamp));
+        sut.insert(create_status("DDDDDD", 48.86055, 2.6, current_timestamp));
+        sut.insert(create_status(
+            "EEEEEE",
+            48.858222,
+            2.2945,
+            current_timestamp - MAX_AGE - 1,
+        ));
+
+        let position = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        let mut result = sut.near(&position, 10.0, |status, _| {
+            Some(status.aircraft.id.clone())
+        });
+        result.sort_unstable();
+
+        assert_eq!(result, ["AAAAAA", "BBBBBB", "CCCCCC"]);
+        assert_eq!(
+            sut.near(&position, 10.0, |_, distance| (distance > 1.0)
+                .then_some(()))
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn insert_keeps_one_expiry_entry_per_key() {
+        let sut = Store::create(MAX_AGE);
+        let current_timestamp = get_current_timestamp();
+
+        for time_stamp in [
+            current_timestamp - 2,
+            current_timestamp - 1,
+            current_timestamp,
+        ] {
+            sut.insert(create_status("AAAAAA", 48.858222, 2.2945, time_stamp));
+            sut.insert(create_status("AAAAAA", 48.858222, 2.2945, time_stamp));
+        }
+
+        let expiry_queue = sut
+            .shards
+            .iter()
+            .map(|shard| shard.read().unwrap())
+            .flat_map(|shard| shard.expiry_queue.clone())
+            .collect::<Vec<_>>();
+
+        assert_eq!(expiry_queue.len(), 1);
+        assert_eq!(expiry_queue[0].0, current_timestamp);
+        assert_eq!(expiry_queue[0].1.len(), 1);
+    }
+
+    #[test]
+    fn remove_outdated_works() {
+        let sut = Store::create(MAX_AGE);
+        let current_timestamp = get_current_timestamp();
+        let outdated_timestamp = current_timestamp - MAX_AGE - 1;
+
+        sut.insert(create_status(
+            "AAAAAA",
+            48.858222,
+            2.2945,
+            outdated_timestamp,
+        ));
+        sut.insert(create_status(
+            "BBBBBB",
+            48.86055,
+            2.3376,
+            outdated_timestamp,
+        ));
+        sut.insert(create_status("BBBBBB", 48.86055, 2.3376, current_timestamp));
+        sut.insert(create_status("CCCCCC", 48.86055, 2.3376, current_timestamp));
+        sut.retain(|status| status.aircraft.id != "CCCCCC");
+
+        assert_eq!(sut.len(), 2);
+        assert_eq!(sut.last_update(), Some(current_timestamp));
+        assert_eq!(sut.remove_outdated(), 1);
+        assert_eq!(sut.remove_outdated(), 0);
+        assert_eq!(sut.len(), 1);
+
+        let shard_sizes = sut
+            .shards
+            .iter()
+            .map(|shard| shard.read().unwrap())
+            .map(|shard| {
+                (
+                    shard.states.len(),
+                    shard.grid.len(),
+                    shard.expiry_queue.values().map(HashSet::len).sum::<usize>(),
+                )
+            })
+            .fold((0, 0, 0), |(a, b, c), (d, e, f)| (a + d, b + e, c + f));
+
+        assert_eq!(shard_sizes, (1, 1, 1));
+    }
+
+    #[test]
+    fn retain_works() {
+        let sut = Store::create(MAX_AGE);
+        let current_timestamp = get_current_timestamp();
+        let position = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        sut.insert(create_status(
+            "AAAAAA",
+            48.858222,
+            2.2945,
+            current_timestamp,
+        ));
+        sut.insert(create_status("BBBBBB", 48.86055, 2.3376, current_timestamp));
+
+        sut.retain(|status| status.aircraft.id != "AAAAAA");
+
+        let result = sut.near(&position, 10.0, |status, _| {
+            Some(status.aircraft.id.clone())
+        });
+
+        assert_eq!(result, ["BBBBBB"]);
+        assert_eq!(
+            sut.shards
+                .iter()
+                .map(|shard| shard.read().unwrap().grid.len())
+                .sum::<usize>(),
+            1
+        );
+    }
+
+    fn create_status(aircraft_id: &str, latitude: f64, longitude: f64, time_stamp: u64) -> Status {
+        Status {
+            aircraft: Aircraft {
+                id: String::from(aircraft_id),
+                ..Default::default()
+            },
+            position: Position {
+                latitude,
+                longitude,
+            },
+            time_stamp,
+            ..Default::default()
+        }
+    }
+}
@@ -1,10 +1,14 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Number of seconds per day
+const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+
 /// Returns current unix timestamp
 ///
 /// # Examples
 ///
 /// ```
+/// # use above_me::time::get_current_timestamp;
 /// assert!(get_current_timestamp() > 0);
 /// ```
 pub fn get_current_timestamp() -> u64 {
@@ -14,6 +18,40 @@ pub fn get_current_timestamp() -> u64 {
         .as_secs()
 }
 
+/// Returns the unix timestamp of a time of day (UTC) that is closest to a reference timestamp.
+/// Useful for values that only contain the time of day (e.g. "HHMMSS" of APRS packets), so the
+/// date has to be derived from the time of receiving them. Handles rollovers at midnight in both
+/// directions, e.g. a packet of 23:59:58 that has been received at 00:00:02 belongs to the
+/// previous day.
+///
+/// # Arguments
+///
+/// * `time_of_day` - Seconds since midnight (UTC)
+/// * `reference` - Reference timestamp, e.g. the time of receiving
+///
+/// # Examples
+///
+/// ```
+/// # use above_me::time::get_timestamp_by_time_of_day;
+/// /* 1970-01-02 00:00:02 */
+/// let reference = 86_402;
+///
+/// assert_eq!(get_timestamp_by_time_of_day(1, reference), 86_401);
+/// assert_eq!(get_timestamp_by_time_of_day(86_398, reference), 86_398);
+/// ```
+pub fn get_timestamp_by_time_of_day(time_of_day: u32, reference: u64) -> u64 {
+    let start_of_day = reference - reference % SECONDS_PER_DAY;
+    let time_stamp = start_of_day + u64::from(time_of_day);
+
+    if time_stamp > reference + SECONDS_PER_DAY / 2 {
+        time_stamp.saturating_sub(SECONDS_PER_DAY)
+    } else if time_stamp + SECONDS_PER_DAY / 2 < reference {
+        time_stamp + SECONDS_PER_DAY
+    } else {
+        time_stamp
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -24,4 +62,40 @@ mod tests {
     fn timestamp_not_empty() {
         assert!(get_current_timestamp() > 0);
     }
+
+    #[test]
+    fn get_timestamp_by_time_of_day_works() {
+        /* 2024-06-01 12:00:00 */
+        let reference = 1_717_243_200;
+        let start_of_day = 1_717_200_000;
+
+        assert_eq!(
+            get_timestamp_by_time_of_day(43_190, reference),
+            start_of_day + 43_190
+        );
+        assert_eq!(
+            get_timestamp_by_time_of_day(43_210, reference),
+            start_of_day + 43_210
+        );
+    }
+
+    #[test]
+    fn get_timestamp_by_time_of_day_handles_midnight() {
+        /* 2024-06-02 00:00:02 */
+        let after_midnight = 1_717_286_402;
+        /* 2024-06-01 23:59:58 */
+        let before_midnight = 1_717_286_398;
+
+        /* Packet of yesterday, received today */
+        assert_eq!(
+            get_timestamp_by_time_of_day(86_398, after_midnight),
+            before_midnight
+        );
+
+        /* Packet of tomorrow (e.g. clock of sender is slightly ahead), received today */
+        assert_eq!(
+            get_timestamp_by_time_of_day(2, before_midnight),
+            after_midnight
+        );
+    }
 }